@@ -0,0 +1,121 @@
+//! Compares evaluating an expression directly with building a tree for it.
+//!
+//! Run with `cargo bench --bench evaluate`.
+
+use pratt::{Affix, Associativity, NoError, PrattParser, Precedence, Result, Tail};
+use std::hint::black_box;
+use std::time::Instant;
+
+enum Expr {
+    BinOp(Box<Expr>, u8, Box<Expr>),
+    Neg(Box<Expr>),
+    Int(i64),
+}
+
+fn query(&op: &u8) -> Result<Affix> {
+    Ok(match op {
+        b'+' | b'-' => Affix::Infix(Precedence(3), Associativity::Left),
+        b'*' => Affix::Infix(Precedence(4), Associativity::Left),
+        b'~' => Affix::Prefix(Precedence(6)),
+        _ => Affix::Nilfix,
+    })
+}
+
+fn apply(lhs: i64, op: u8, rhs: i64) -> i64 {
+    match op {
+        b'+' => lhs.wrapping_add(rhs),
+        b'-' => lhs.wrapping_sub(rhs),
+        _ => lhs.wrapping_mul(rhs),
+    }
+}
+
+struct TreeParser;
+
+impl<I: Iterator<Item = u8>> PrattParser<I> for TreeParser {
+    type Error = NoError;
+    type Input = u8;
+    type Output = Expr;
+    type Position = ();
+
+    fn query(&mut self, op: &u8) -> Result<Affix> {
+        query(op)
+    }
+
+    fn primary(&mut self, digit: u8, _: &mut Tail<'_, I>) -> Result<Expr> {
+        Ok(Expr::Int((digit - b'0') as i64))
+    }
+
+    fn infix(&mut self, lhs: Expr, op: u8, rhs: Expr) -> Result<Expr> {
+        Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    fn prefix(&mut self, _: u8, rhs: Expr) -> Result<Expr> {
+        Ok(Expr::Neg(Box::new(rhs)))
+    }
+
+    fn postfix(&mut self, _: Expr, _: u8) -> Result<Expr> {
+        unreachable!()
+    }
+}
+
+fn eval(expr: &Expr) -> i64 {
+    match expr {
+        Expr::BinOp(lhs, op, rhs) => apply(eval(lhs), *op, eval(rhs)),
+        Expr::Neg(rhs) => eval(rhs).wrapping_neg(),
+        Expr::Int(i) => *i,
+    }
+}
+
+struct Calculator;
+
+impl<I: Iterator<Item = u8>> PrattParser<I> for Calculator {
+    type Error = NoError;
+    type Input = u8;
+    type Output = i64;
+    type Position = ();
+
+    fn query(&mut self, op: &u8) -> Result<Affix> {
+        query(op)
+    }
+
+    fn primary(&mut self, digit: u8, _: &mut Tail<'_, I>) -> Result<i64> {
+        Ok((digit - b'0') as i64)
+    }
+
+    fn infix(&mut self, lhs: i64, op: u8, rhs: i64) -> Result<i64> {
+        Ok(apply(lhs, op, rhs))
+    }
+
+    fn prefix(&mut self, _: u8, rhs: i64) -> Result<i64> {
+        Ok(rhs.wrapping_neg())
+    }
+
+    fn postfix(&mut self, _: i64, _: u8) -> Result<i64> {
+        unreachable!()
+    }
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut() -> i64) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    println!("{:<8} {:?}/iter", name, start.elapsed() / iterations);
+}
+
+fn main() {
+    let input = "1+2*~3-4*5*6+~7+".repeat(1000) + "0";
+    let input = input.as_bytes();
+    let tree = eval(&TreeParser.parse(input.iter().copied()).unwrap());
+    let value = Calculator.evaluate(input.iter().copied()).unwrap();
+    assert_eq!(tree, value);
+
+    bench("tree", 100, || {
+        eval(&TreeParser.parse(black_box(input).iter().copied()).unwrap())
+    });
+    bench("evaluate", 100, || {
+        Calculator
+            .evaluate(black_box(input).iter().copied())
+            .unwrap()
+    });
+}
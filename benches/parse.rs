@@ -0,0 +1,87 @@
+//! Measures the parse loop on wide and on deeply nested expressions, with
+//! the recursive and the iterative parser.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use pratt::{Affix, Associativity, NoError, PrattError, PrattParser, Precedence, Result, Tail};
+use std::hint::black_box;
+use std::time::Instant;
+
+/// Counts the primaries of an expression, so that the parse loop dominates.
+struct Counter;
+
+impl<I: Iterator<Item = u8>> PrattParser<I> for Counter {
+    type Error = NoError;
+    type Input = u8;
+    type Output = u64;
+    type Position = ();
+
+    fn query(&mut self, op: &u8) -> Result<Affix> {
+        Ok(match op {
+            b'+' => Affix::Infix(Precedence(3), Associativity::Left),
+            b'^' => Affix::Infix(Precedence(7), Associativity::Right),
+            b'~' => Affix::Prefix(Precedence(6)),
+            b'(' => Affix::Circumfix,
+            _ => Affix::Nilfix,
+        })
+    }
+
+    fn primary(&mut self, _: u8, _: &mut Tail<'_, I>) -> Result<u64> {
+        Ok(1)
+    }
+
+    fn infix(&mut self, lhs: u64, _: u8, rhs: u64) -> Result<u64> {
+        Ok(lhs + rhs)
+    }
+
+    fn prefix(&mut self, _: u8, rhs: u64) -> Result<u64> {
+        Ok(rhs)
+    }
+
+    fn postfix(&mut self, _: u64, _: u8) -> Result<u64> {
+        unreachable!()
+    }
+
+    fn circumfix(&mut self, _: u8, inner: u64, _: u8) -> Result<u64, PrattError<u8, NoError>> {
+        Ok(inner)
+    }
+
+    fn closes(&open: &u8, &close: &u8) -> bool {
+        open == b'(' && close == b')'
+    }
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut() -> u64) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    println!("{:<24} {:?}/iter", name, start.elapsed() / iterations);
+}
+
+fn main() {
+    let inputs = [
+        ("wide", "1+".repeat(10_000) + "1", 10_001),
+        ("left", "1+(1+1)+".repeat(10_000) + "1", 30_001),
+        ("right", "1^".repeat(1_000) + "1", 1_001),
+        ("prefix", "~".repeat(1_000) + "1", 1),
+        ("parens", "(".repeat(1_000) + "1" + &")".repeat(1_000), 1),
+    ];
+    for (name, input, primaries) in &inputs {
+        let input = input.as_bytes();
+        assert_eq!(Counter.parse(input.iter().copied()).unwrap(), *primaries);
+        assert_eq!(
+            Counter.parse_iterative(input.iter().copied()).unwrap(),
+            *primaries
+        );
+
+        bench(&format!("{} parse", name), 100, || {
+            Counter.parse(black_box(input).iter().copied()).unwrap()
+        });
+        bench(&format!("{} parse_iterative", name), 100, || {
+            Counter
+                .parse_iterative(black_box(input).iter().copied())
+                .unwrap()
+        });
+    }
+}
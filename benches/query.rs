@@ -0,0 +1,79 @@
+//! Counts how often a parser queries its inputs, with a `query` that looks
+//! operators up by name as a parser over string tokens would.
+//!
+//! Run with `cargo bench --bench query`.
+
+use pratt::{Affix, Associativity, NoError, PrattParser, Precedence, Result, Tail};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::Instant;
+
+struct Calculator {
+    operators: HashMap<&'static str, Affix>,
+    queries: usize,
+}
+
+impl Calculator {
+    fn new() -> Self {
+        let operators = HashMap::from([
+            ("plus", Affix::Infix(Precedence(3), Associativity::Left)),
+            ("minus", Affix::Infix(Precedence(3), Associativity::Left)),
+            ("times", Affix::Infix(Precedence(4), Associativity::Left)),
+            ("pow", Affix::Infix(Precedence(7), Associativity::Right)),
+            ("neg", Affix::Prefix(Precedence(6))),
+        ]);
+        Calculator {
+            operators,
+            queries: 0,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a str>> PrattParser<I> for Calculator {
+    type Error = NoError;
+    type Input = &'a str;
+    type Output = i64;
+    type Position = ();
+
+    fn query(&mut self, word: &&'a str) -> Result<Affix> {
+        self.queries += 1;
+        Ok(self.operators.get(word).copied().unwrap_or(Affix::Nilfix))
+    }
+
+    fn primary(&mut self, word: &'a str, _: &mut Tail<'_, I>) -> Result<i64> {
+        word.parse().map_err(|_| NoError)
+    }
+
+    fn infix(&mut self, lhs: i64, op: &'a str, rhs: i64) -> Result<i64> {
+        Ok(match op {
+            "plus" => lhs.wrapping_add(rhs),
+            "minus" => lhs.wrapping_sub(rhs),
+            "times" => lhs.wrapping_mul(rhs),
+            _ => lhs.wrapping_pow(rhs as u32),
+        })
+    }
+
+    fn prefix(&mut self, _: &'a str, rhs: i64) -> Result<i64> {
+        Ok(rhs.wrapping_neg())
+    }
+
+    fn postfix(&mut self, _: i64, _: &'a str) -> Result<i64> {
+        unreachable!()
+    }
+}
+
+fn main() {
+    let input = "1 times 2 pow 3 pow 2 plus neg 4 times 5 minus 6 plus ".repeat(1000) + "0";
+    let words: Vec<&str> = input.split(' ').collect();
+
+    let mut calculator = Calculator::new();
+    calculator.evaluate(words.iter().copied()).unwrap();
+    println!("{} queries for {} inputs", calculator.queries, words.len());
+
+    let iterations = 100;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(calculator.evaluate(black_box(&words).iter().copied())).unwrap();
+    }
+    println!("evaluate {:?}/iter", start.elapsed() / iterations);
+}
@@ -0,0 +1,201 @@
+//! Measures `ExprParser::parse` of the lalrpop example, on wide, deeply
+//! nested and mixed token trees, and on trees of only left-associative
+//! operators.
+//!
+//! The token trees are built directly rather than by the grammar of the
+//! example, so that only the Pratt parser is measured, apart from a clone of
+//! the trees which each parse consumes.
+//!
+//! Run with `cargo bench --bench token_tree`.
+
+use pratt::{Affix, Associativity, PrattParser, Precedence, Result, Tail};
+use std::hint::black_box;
+use std::time::Instant;
+
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    BinOp(Box<Expr>, BinOpKind, Box<Expr>),
+    UnOp(UnOpKind, Box<Expr>),
+    Int(i32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BinOpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Eq,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UnOpKind {
+    Not,
+    Neg,
+    Try,
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenTree {
+    Prefix(char),
+    Postfix(char),
+    Infix(char),
+    Primary(i32),
+    Group(Vec<TokenTree>),
+}
+
+struct ExprParser;
+
+impl<I> PrattParser<I> for ExprParser
+where
+    I: Iterator<Item = TokenTree>,
+{
+    type Error = pratt::NoError;
+    type Input = TokenTree;
+    type Output = Expr;
+    type Position = ();
+
+    fn query(&mut self, tree: &TokenTree) -> Result<Affix> {
+        let affix = match tree {
+            TokenTree::Infix('=') => Affix::Infix(Precedence(2), Associativity::Neither),
+            TokenTree::Infix('+') => Affix::Infix(Precedence(3), Associativity::Left),
+            TokenTree::Infix('-') => Affix::Infix(Precedence(3), Associativity::Left),
+            TokenTree::Infix('*') => Affix::Infix(Precedence(4), Associativity::Left),
+            TokenTree::Infix('/') => Affix::Infix(Precedence(4), Associativity::Left),
+            TokenTree::Postfix('?') => Affix::Postfix(Precedence(5)),
+            TokenTree::Prefix('-') => Affix::Prefix(Precedence(6)),
+            TokenTree::Prefix('!') => Affix::Prefix(Precedence(6)),
+            TokenTree::Infix('^') => Affix::Infix(Precedence(7), Associativity::Right),
+            TokenTree::Group(_) => Affix::Nilfix,
+            TokenTree::Primary(_) => Affix::Nilfix,
+            _ => unreachable!(),
+        };
+        Ok(affix)
+    }
+
+    fn primary(&mut self, tree: TokenTree, _tail: &mut Tail<'_, I>) -> Result<Expr> {
+        let expr = match tree {
+            TokenTree::Primary(num) => Expr::Int(num),
+            TokenTree::Group(group) => self.parse(group).unwrap(),
+            _ => unreachable!(),
+        };
+        Ok(expr)
+    }
+
+    fn infix(&mut self, lhs: Expr, tree: TokenTree, rhs: Expr) -> Result<Expr> {
+        let op = match tree {
+            TokenTree::Infix('+') => BinOpKind::Add,
+            TokenTree::Infix('-') => BinOpKind::Sub,
+            TokenTree::Infix('*') => BinOpKind::Mul,
+            TokenTree::Infix('/') => BinOpKind::Div,
+            TokenTree::Infix('^') => BinOpKind::Pow,
+            TokenTree::Infix('=') => BinOpKind::Eq,
+            _ => unreachable!(),
+        };
+        Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    fn prefix(&mut self, tree: TokenTree, rhs: Expr) -> Result<Expr> {
+        let op = match tree {
+            TokenTree::Prefix('!') => UnOpKind::Not,
+            TokenTree::Prefix('-') => UnOpKind::Neg,
+            _ => unreachable!(),
+        };
+        Ok(Expr::UnOp(op, Box::new(rhs)))
+    }
+
+    fn postfix(&mut self, lhs: Expr, tree: TokenTree) -> Result<Expr> {
+        let op = match tree {
+            TokenTree::Postfix('?') => UnOpKind::Try,
+            _ => unreachable!(),
+        };
+        Ok(Expr::UnOp(op, Box::new(lhs)))
+    }
+}
+
+/// `1+1+...+1` with `n` operators.
+fn wide(n: usize) -> Vec<TokenTree> {
+    let mut trees = vec![TokenTree::Primary(1)];
+    for _ in 0..n {
+        trees.push(TokenTree::Infix('+'));
+        trees.push(TokenTree::Primary(1));
+    }
+    trees
+}
+
+/// `((...(1)...))` nested `n` deep.
+fn deep(n: usize) -> Vec<TokenTree> {
+    let mut trees = vec![TokenTree::Primary(1)];
+    for _ in 0..n {
+        trees = vec![TokenTree::Group(trees)];
+    }
+    trees
+}
+
+/// `!1?*-3+3/!2^4?-1` from the README, joined by `+` `n` times.
+fn mixed(n: usize) -> Vec<TokenTree> {
+    use TokenTree::*;
+    let expr = [
+        Prefix('!'),
+        Primary(1),
+        Postfix('?'),
+        Infix('*'),
+        Prefix('-'),
+        Primary(3),
+        Infix('+'),
+        Primary(3),
+        Infix('/'),
+        Prefix('!'),
+        Primary(2),
+        Infix('^'),
+        Primary(4),
+        Postfix('?'),
+        Infix('-'),
+        Primary(1),
+    ];
+    let mut trees = expr.to_vec();
+    for _ in 0..n {
+        trees.push(Infix('+'));
+        trees.extend(expr.iter().cloned());
+    }
+    trees
+}
+
+/// `1+(2*3-4)/5+...` with `n` groups, where every operator is
+/// left-associative.
+fn left(n: usize) -> Vec<TokenTree> {
+    use TokenTree::*;
+    let group = [Primary(2), Infix('*'), Primary(3), Infix('-'), Primary(4)];
+    let mut trees = vec![Primary(1)];
+    for _ in 0..n {
+        trees.push(Infix('+'));
+        trees.push(Group(group.to_vec()));
+        trees.push(Infix('/'));
+        trees.push(Primary(5));
+    }
+    trees
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut() -> Expr) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    println!("{:<24} {:?}/iter", name, start.elapsed() / iterations);
+}
+
+fn main() {
+    let inputs = [
+        ("wide", wide(10_000)),
+        ("deep", deep(1_000)),
+        ("mixed", mixed(1_000)),
+        ("left", left(10_000)),
+    ];
+    for (name, trees) in &inputs {
+        ExprParser.parse(trees.clone()).unwrap();
+        bench(name, 100, || {
+            ExprParser.parse(black_box(trees.clone())).unwrap()
+        });
+    }
+}
@@ -0,0 +1,303 @@
+use pratt::{operators, Affix, BindingPower, PrattError, PrattParser, Precedence, Tail};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Expr {
+    BinOp(Box<Expr>, BinOpKind, Box<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Int(i32),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum BinOpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+// Prints an expression as an S-expression, e.g. `(* (+ 1 2) 3)`
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expr::BinOp(lhs, op, rhs) => {
+                let op = match op {
+                    BinOpKind::Add => '+',
+                    BinOpKind::Sub => '-',
+                    BinOpKind::Mul => '*',
+                    BinOpKind::Div => '/',
+                    BinOpKind::Pow => '^',
+                };
+                write!(f, "({} {} {})", op, lhs, rhs)
+            }
+            Expr::Index(lhs, index) => write!(f, "([] {} {})", lhs, index),
+            Expr::Neg(rhs) => write!(f, "(- {})", rhs),
+            Expr::Int(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Token {
+    Num(i32),
+    Op(char),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+}
+
+// A flat token stream, without any grouping pass
+fn lex(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '0'..='9' => {
+                let mut num = c.to_digit(10).unwrap() as i32;
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    num = num * 10 + d as i32;
+                    chars.next();
+                }
+                Token::Num(num)
+            }
+            c if c.is_whitespace() => continue,
+            c => Token::Op(c),
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct ExprParser;
+
+// The operator table, where a `-` which follows an operand is a subtraction
+// and any other is a negation
+fn affix(token: &Token, led: bool) -> Result<Affix, String> {
+    Ok(operators!(token, led;
+        Token::Op('+') => Infix(3, Left),
+        Token::Op('-') => Prefix(6),
+        led Token::Op('-') => Infix(3, Left),
+        Token::Op('*' | '/') => Infix(4, Left),
+        Token::Op('^') => Infix(7, Right),
+        Token::LBracket => PostfixWith(8),
+        Token::Num(_) | Token::LParen | Token::RParen | Token::RBracket => Nilfix,
+        Token::Op(c) => { return Err(format!("Unknown operator {}", c)) },
+    ))
+}
+
+impl<I> PrattParser<I> for ExprParser
+where
+    I: Iterator<Item = Token>,
+{
+    type Error = String;
+    type Input = Token;
+    type Output = Expr;
+    type Position = ();
+
+    // Query information about an operator (Affix, Precedence, Associativity)
+    fn query(&mut self, token: &Token) -> Result<Affix, String> {
+        affix(token, false)
+    }
+
+    fn query_led(&mut self, token: &Token) -> Result<Option<Affix>, String> {
+        affix(token, true).map(Some)
+    }
+
+    // Construct a primary expression, e.g. a number or a parenthesized group
+    fn primary(&mut self, token: Token, tail: &mut Tail<'_, I>) -> Result<Expr, String> {
+        match token {
+            Token::Num(num) => Ok(Expr::Int(num)),
+            Token::LParen => {
+                let mut stop = |token: &Token| *token == Token::RParen;
+                let expr = self
+                    .parse_input(&mut tail.until(&mut stop), Precedence::MIN)
+                    .map_err(|e| e.to_string())?;
+                match tail.next_if(|token| *token == Token::RParen) {
+                    Some(_) => Ok(expr),
+                    None => Err("Expected )".to_string()),
+                }
+            }
+            _ => Err(format!("Unexpected {:?}", token)),
+        }
+    }
+
+    // Construct a binary infix expression, e.g. 1+1. An operator which
+    // `affix` classifies as infix but which is not handled here is an error
+    // rather than a panic
+    fn infix(&mut self, lhs: Expr, token: Token, rhs: Expr) -> Result<Expr, String> {
+        let op = match token {
+            Token::Op('+') => BinOpKind::Add,
+            Token::Op('-') => BinOpKind::Sub,
+            Token::Op('*') => BinOpKind::Mul,
+            Token::Op('/') => BinOpKind::Div,
+            Token::Op('^') => BinOpKind::Pow,
+            _ => return Err(format!("Unhandled infix operator {:?}", token)),
+        };
+        Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    // Construct a negation, e.g. -1
+    fn prefix(&mut self, _token: Token, rhs: Expr) -> Result<Expr, String> {
+        Ok(Expr::Neg(Box::new(rhs)))
+    }
+
+    fn postfix(&mut self, _lhs: Expr, token: Token) -> Result<Expr, String> {
+        Err(format!("Unhandled postfix operator {:?}", token))
+    }
+
+    // Construct an indexing expression, e.g. 1[2], parsing the index up to the ]
+    fn postfix_with(
+        &mut self,
+        lhs: Expr,
+        _token: Token,
+        tail: &mut Tail<'_, I>,
+    ) -> Result<Expr, PrattError<Token, String>> {
+        let mut stop = |token: &Token| *token == Token::RBracket;
+        let index = self.parse_input(&mut tail.until(&mut stop), Precedence::MIN)?;
+        match tail.next_if(|token| *token == Token::RBracket) {
+            Some(_) => Ok(Expr::Index(Box::new(lhs), Box::new(index))),
+            None => Err("Expected ]".to_string().into()),
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args();
+    let _ = args.next();
+
+    let input = args.next().expect("Expected input string");
+    println!("Code: {}", input);
+
+    let tokens = lex(&input);
+    println!("Tokens: {:?}", tokens);
+
+    let expr = ExprParser.parse(tokens).unwrap();
+    println!("Expression: {}", expr);
+}
+
+#[cfg(test)]
+mod test {
+    fn parse(input: &str) -> Result<Expr, String> {
+        ExprParser.parse(lex(input)).map_err(|e| e.to_string())
+    }
+    use super::BinOpKind::*;
+    use super::Expr::*;
+    use super::*;
+
+    #[test]
+    fn test1() {
+        assert_eq!(
+            parse("(1 + 2) * 3"),
+            Ok(BinOp(
+                Box::new(BinOp(Box::new(Int(1)), Add, Box::new(Int(2)))),
+                Mul,
+                Box::new(Int(3))
+            ))
+        );
+    }
+
+    #[test]
+    fn test2() {
+        assert_eq!(
+            parse("2 ^ (10 - (4 / 2))"),
+            Ok(BinOp(
+                Box::new(Int(2)),
+                Pow,
+                Box::new(BinOp(
+                    Box::new(Int(10)),
+                    Sub,
+                    Box::new(BinOp(Box::new(Int(4)), Div, Box::new(Int(2))))
+                ))
+            ))
+        );
+    }
+
+    #[test]
+    fn test3() {
+        assert_eq!(parse("(1 + 2"), Err("Expected )".to_string()));
+    }
+
+    #[test]
+    fn test4() {
+        for input in [
+            "(1 + 2) * 3",
+            "2 ^ (10 - (4 / 2))",
+            "(1 + 2",
+            "2 ^ 3 ^ 4 - 1",
+            "-1 - -2 ^ -3",
+        ] {
+            let iterative = ExprParser
+                .parse_iterative(lex(input))
+                .map_err(|e| e.to_string());
+            assert_eq!(iterative, parse(input));
+        }
+    }
+
+    #[test]
+    fn test5() {
+        assert_eq!(
+            parse("10[1 + 2][3] * 2"),
+            Ok(BinOp(
+                Box::new(Index(
+                    Box::new(Index(
+                        Box::new(Int(10)),
+                        Box::new(BinOp(Box::new(Int(1)), Add, Box::new(Int(2))))
+                    )),
+                    Box::new(Int(3))
+                )),
+                Mul,
+                Box::new(Int(2))
+            ))
+        );
+        assert_eq!(parse("1[2"), Err("Expected ]".to_string()));
+    }
+
+    #[test]
+    fn test6() {
+        assert_eq!(
+            parse("-1 - -2 ^ -3"),
+            Ok(BinOp(
+                Box::new(Neg(Box::new(Int(1)))),
+                Sub,
+                Box::new(Neg(Box::new(BinOp(
+                    Box::new(Int(2)),
+                    Pow,
+                    Box::new(Neg(Box::new(Int(3))))
+                ))))
+            ))
+        );
+        assert_eq!(
+            parse("1 - (-2)"),
+            Ok(BinOp(
+                Box::new(Int(1)),
+                Sub,
+                Box::new(Neg(Box::new(Int(2))))
+            ))
+        );
+    }
+
+    #[test]
+    fn test7() {
+        let show = |input| parse(input).unwrap().to_string();
+        assert_eq!(show("(1 + 2) * 3"), "(* (+ 1 2) 3)");
+        assert_eq!(show("-(1)[(2 - 3)] ^ 4"), "(- (^ ([] 1 (- 2 3)) 4))");
+    }
+
+    #[test]
+    fn test8() {
+        let mut parser = ExprParser;
+        let result = PrattParser::<std::vec::IntoIter<Token>>::infix(
+            &mut parser,
+            Int(1),
+            Token::Op('%'),
+            Int(2),
+        );
+        assert_eq!(result, Err("Unhandled infix operator Op('%')".to_string()));
+    }
+}
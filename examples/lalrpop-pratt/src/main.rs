@@ -1,5 +1,5 @@
 use lalrpop_util::lalrpop_mod;
-use pratt::{Affix, Associativity, PrattParser, Precedence, Result};
+use pratt::{Affix, Associativity, PrattParser, Precedence, Result, Tail};
 
 lalrpop_mod!(pub grammar);
 
@@ -42,12 +42,13 @@ impl<I> PrattParser<I> for ExprParser
 where
     I: Iterator<Item = TokenTree>,
 {
-    type Error = pratt::NoError;
+    type Error = String;
     type Input = TokenTree;
     type Output = Expr;
+    type Position = ();
 
     // Query information about an operator (Affix, Precedence, Associativity)
-    fn query(&mut self, tree: &TokenTree) -> Result<Affix> {
+    fn query(&mut self, tree: &TokenTree) -> Result<Affix, String> {
         let affix = match tree {
             TokenTree::Infix('=') => Affix::Infix(Precedence(2), Associativity::Neither),
             TokenTree::Infix('+') => Affix::Infix(Precedence(3), Associativity::Left),
@@ -60,23 +61,23 @@ where
             TokenTree::Infix('^') => Affix::Infix(Precedence(7), Associativity::Right),
             TokenTree::Group(_) => Affix::Nilfix,
             TokenTree::Primary(_) => Affix::Nilfix,
-            _ => unreachable!(),
+            _ => return Err(format!("Unknown operator {:?}", tree)),
         };
         Ok(affix)
     }
 
     // Construct a primary expression, e.g. a number
-    fn primary(&mut self, tree: TokenTree) -> Result<Expr> {
+    fn primary(&mut self, tree: TokenTree, _tail: &mut Tail<'_, I>) -> Result<Expr, String> {
         let expr = match tree {
             TokenTree::Primary(num) => Expr::Int(num),
-            TokenTree::Group(group) => self.parse(&mut group.into_iter()).unwrap(),
-            _ => unreachable!(),
+            TokenTree::Group(group) => self.parse(group).map_err(|error| error.to_string())?,
+            _ => return Err(format!("Unhandled primary {:?}", tree)),
         };
         Ok(expr)
     }
 
     // Construct a binary infix expression, e.g. 1+1
-    fn infix(&mut self, lhs: Expr, tree: TokenTree, rhs: Expr) -> Result<Expr> {
+    fn infix(&mut self, lhs: Expr, tree: TokenTree, rhs: Expr) -> Result<Expr, String> {
         let op = match tree {
             TokenTree::Infix('+') => BinOpKind::Add,
             TokenTree::Infix('-') => BinOpKind::Sub,
@@ -84,26 +85,26 @@ where
             TokenTree::Infix('/') => BinOpKind::Div,
             TokenTree::Infix('^') => BinOpKind::Pow,
             TokenTree::Infix('=') => BinOpKind::Eq,
-            _ => unreachable!(),
+            _ => return Err(format!("Unhandled infix operator {:?}", tree)),
         };
         Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
     }
 
     // Construct a unary prefix expression, e.g. !1
-    fn prefix(&mut self, tree: TokenTree, rhs: Expr) -> Result<Expr> {
+    fn prefix(&mut self, tree: TokenTree, rhs: Expr) -> Result<Expr, String> {
         let op = match tree {
             TokenTree::Prefix('!') => UnOpKind::Not,
             TokenTree::Prefix('-') => UnOpKind::Neg,
-            _ => unreachable!(),
+            _ => return Err(format!("Unhandled prefix operator {:?}", tree)),
         };
         Ok(Expr::UnOp(op, Box::new(rhs)))
     }
 
     // Construct a unary postfix expression, e.g. 1?
-    fn postfix(&mut self, lhs: Expr, tree: TokenTree) -> Result<Expr> {
+    fn postfix(&mut self, lhs: Expr, tree: TokenTree) -> Result<Expr, String> {
         let op = match tree {
             TokenTree::Postfix('?') => UnOpKind::Try,
-            _ => unreachable!(),
+            _ => return Err(format!("Unhandled postfix operator {:?}", tree)),
         };
         Ok(Expr::UnOp(op, Box::new(lhs)))
     }
@@ -119,18 +120,15 @@ fn main() {
     let tt = grammar::TokenTreeParser::new().parse(&input).unwrap();
     println!("TokenTree: {:?}", tt);
 
-    let expr = ExprParser.parse(tt.into_iter()).unwrap();
+    let expr = ExprParser.parse(tt).unwrap();
     println!("Expression: {:?}", expr);
 }
 
 #[cfg(test)]
 mod test {
     fn parse(input: &str) -> Expr {
-        let tt = grammar::TokenTreeParser::new()
-            .parse(input)
-            .unwrap()
-            .into_iter();
-        ExprParser.parse(tt.into_iter()).unwrap()
+        let tt = grammar::TokenTreeParser::new().parse(input).unwrap();
+        ExprParser.parse(tt).unwrap()
     }
     use super::BinOpKind::*;
     use super::Expr::*;
@@ -139,10 +137,11 @@ mod test {
 
     #[test]
     fn test1() {
-        assert_eq!(
-            parse("1=2=3"),
-            BinOp(Box::new(Int(1)), Eq, Box::new(Int(2)))
-        );
+        let tt = grammar::TokenTreeParser::new().parse("1=2=3").unwrap();
+        assert!(matches!(
+            ExprParser.parse(tt),
+            Err(pratt::PrattError::NonAssociative(..))
+        ));
     }
 
     #[test]
@@ -198,4 +197,14 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn unknown_operator() {
+        use TokenTree::*;
+        let tt = vec![Primary(1), Infix('%'), Primary(2)];
+        assert!(matches!(
+            ExprParser.parse(tt),
+            Err(pratt::PrattError::UserError(error)) if error == "Unknown operator Infix('%')"
+        ));
+    }
 }
@@ -0,0 +1,321 @@
+use logos::{Logos, SpannedIter};
+use pratt::{Affix, Associativity, PrattError, PrattParser, Precedence, Result, Tail};
+use std::ops::Range;
+
+#[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(skip r"[ \t\n]+")]
+pub enum Token {
+    #[token("=")]
+    Eq,
+    #[token("≠")]
+    #[token("!=")]
+    Ne,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    #[token("−")]
+    Minus,
+    #[token("*")]
+    #[token("×")]
+    Star,
+    #[token("/")]
+    #[token("÷")]
+    Slash,
+    #[token("^")]
+    #[token("**")]
+    Caret,
+    #[token("?")]
+    Question,
+    #[token("!")]
+    Bang,
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[regex("[0-9]+", |lex| lex.slice().parse().ok())]
+    Int(i32),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Expr {
+    BinOp(Box<Expr>, BinOpKind, Box<Expr>),
+    UnOp(UnOpKind, Box<Expr>),
+    Int(i32),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum BinOpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum UnOpKind {
+    Not,
+    Neg,
+    Try,
+}
+
+/// An input which logos could not match to a token, or a token out of
+/// place, e.g. a `)` in operand position.
+#[derive(Debug)]
+pub enum InputError {
+    Unrecognized,
+    Unexpected(Token),
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InputError::Unrecognized => write!(f, "unrecognized input"),
+            InputError::Unexpected(token) => write!(f, "unexpected {:?}", token),
+        }
+    }
+}
+
+/// The error for a token which a callback does not handle.
+fn unexpected(token: std::result::Result<Token, ()>) -> InputError {
+    match token {
+        Ok(token) => InputError::Unexpected(token),
+        Err(()) => InputError::Unrecognized,
+    }
+}
+
+/// A token, or a lexer error, with the byte range it was lexed from.
+type Spanned = (std::result::Result<Token, ()>, Range<usize>);
+
+struct ExprParser;
+
+// The lexer yields `Result<Token, ()>`, which is used as the input as is, so
+// that lexer errors are reported by `query` and end up in
+// `PrattError::UserError`. Its `spanned` iterator pairs each with its span,
+// which errors point at through `position`.
+impl<I> PrattParser<I> for ExprParser
+where
+    I: Iterator<Item = Spanned>,
+{
+    type Error = InputError;
+    type Input = Spanned;
+    type Output = Expr;
+    type Position = Range<usize>;
+
+    // Query information about an operator (Affix, Precedence, Associativity)
+    fn query(&mut self, (token, _): &Spanned) -> Result<Affix, InputError> {
+        let affix = match token.as_ref().map_err(|_| InputError::Unrecognized)? {
+            Token::Eq | Token::Ne => Affix::Infix(Precedence(2), Associativity::Neither),
+            Token::Plus => Affix::Infix(Precedence(3), Associativity::Left),
+            Token::Star => Affix::Infix(Precedence(4), Associativity::Left),
+            Token::Slash => Affix::Infix(Precedence(4), Associativity::Left),
+            Token::Question => Affix::Postfix(Precedence(5)),
+            Token::Minus => Affix::Prefix(Precedence(6)),
+            Token::Bang => Affix::Prefix(Precedence(6)),
+            Token::Caret => Affix::Infix(Precedence(7), Associativity::Right),
+            Token::LParen => Affix::Circumfix,
+            Token::RParen => Affix::Nilfix,
+            Token::Int(_) => Affix::Nilfix,
+        };
+        Ok(affix)
+    }
+
+    // The lexer has a single `-`, which is infix after an operand
+    fn query_led(&mut self, input: &Spanned) -> Result<Option<Affix>, InputError> {
+        match input {
+            (Ok(Token::Minus), _) => Ok(Some(Affix::Infix(Precedence(3), Associativity::Left))),
+            _ => PrattParser::<I>::classify(self, input),
+        }
+    }
+
+    // Errors point at the bytes of the offending token
+    fn position(&self, (_, span): &Spanned) -> Range<usize> {
+        span.clone()
+    }
+
+    // Construct a primary expression, e.g. a number
+    fn primary(
+        &mut self,
+        (token, _): Spanned,
+        _tail: &mut Tail<'_, I>,
+    ) -> Result<Expr, InputError> {
+        match token {
+            Ok(Token::Int(i)) => Ok(Expr::Int(i)),
+            token => Err(unexpected(token)),
+        }
+    }
+
+    // Construct a binary infix expression, e.g. 1+1
+    fn infix(&mut self, lhs: Expr, (token, _): Spanned, rhs: Expr) -> Result<Expr, InputError> {
+        let op = match token {
+            Ok(Token::Plus) => BinOpKind::Add,
+            Ok(Token::Minus) => BinOpKind::Sub,
+            Ok(Token::Star) => BinOpKind::Mul,
+            Ok(Token::Slash) => BinOpKind::Div,
+            Ok(Token::Caret) => BinOpKind::Pow,
+            Ok(Token::Eq) => BinOpKind::Eq,
+            Ok(Token::Ne) => BinOpKind::Ne,
+            token => return Err(unexpected(token)),
+        };
+        Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    // Construct a unary prefix expression, e.g. !1
+    fn prefix(&mut self, (token, _): Spanned, rhs: Expr) -> Result<Expr, InputError> {
+        let op = match token {
+            Ok(Token::Bang) => UnOpKind::Not,
+            Ok(Token::Minus) => UnOpKind::Neg,
+            token => return Err(unexpected(token)),
+        };
+        Ok(Expr::UnOp(op, Box::new(rhs)))
+    }
+
+    // Construct a unary postfix expression, e.g. 1?
+    fn postfix(&mut self, lhs: Expr, (token, _): Spanned) -> Result<Expr, InputError> {
+        let op = match token {
+            Ok(Token::Question) => UnOpKind::Try,
+            token => return Err(unexpected(token)),
+        };
+        Ok(Expr::UnOp(op, Box::new(lhs)))
+    }
+
+    // Parentheses only group
+    fn circumfix(
+        &mut self,
+        _: Spanned,
+        inner: Expr,
+        _: Spanned,
+    ) -> Result<Expr, PrattError<Spanned, InputError, Range<usize>>> {
+        Ok(inner)
+    }
+
+    fn closes((open, _): &Spanned, (close, _): &Spanned) -> bool {
+        matches!((open, close), (Ok(Token::LParen), Ok(Token::RParen)))
+    }
+}
+
+fn lex(input: &str) -> SpannedIter<'_, Token> {
+    Token::lexer(input).spanned()
+}
+
+fn main() {
+    let mut args = std::env::args();
+    let _ = args.next();
+
+    let input = args.next().expect("Expected input string");
+    println!("Code: {}", input);
+
+    let expr = ExprParser.parse(lex(&input)).unwrap();
+    println!("Expression: {:?}", expr);
+}
+
+#[cfg(test)]
+mod test {
+    fn parse(input: &str) -> Expr {
+        ExprParser.parse(lex(input)).unwrap()
+    }
+    use super::BinOpKind::*;
+    use super::Expr::*;
+    use super::UnOpKind::*;
+    use super::*;
+
+    #[test]
+    fn test1() {
+        assert!(matches!(
+            ExprParser.parse(lex("1=2=3")),
+            Err(pratt::PrattError::NonAssociative(..))
+        ));
+    }
+
+    #[test]
+    fn test2() {
+        assert_eq!(
+            parse("1*2+3"),
+            BinOp(
+                Box::new(BinOp(Box::new(Int(1)), Mul, Box::new(Int(2)))),
+                Add,
+                Box::new(Int(3))
+            )
+        );
+    }
+
+    #[test]
+    fn test3() {
+        assert_eq!(
+            parse("-1? * !2 ^ 3"),
+            BinOp(
+                Box::new(UnOp(Try, Box::new(UnOp(Neg, Box::new(Int(1)))))),
+                Mul,
+                Box::new(UnOp(
+                    Not,
+                    Box::new(BinOp(Box::new(Int(2)), Pow, Box::new(Int(3))))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test4() {
+        assert_eq!(
+            parse("-1?*!2^3+3/2?-1"),
+            BinOp(
+                Box::new(BinOp(
+                    Box::new(BinOp(
+                        Box::new(UnOp(Try, Box::new(UnOp(Neg, Box::new(Int(1)))))),
+                        Mul,
+                        Box::new(UnOp(
+                            Not,
+                            Box::new(BinOp(Box::new(Int(2)), Pow, Box::new(Int(3))))
+                        ))
+                    )),
+                    Add,
+                    Box::new(BinOp(
+                        Box::new(Int(3)),
+                        Div,
+                        Box::new(UnOp(Try, Box::new(Int(2))))
+                    ))
+                )),
+                Sub,
+                Box::new(Int(1))
+            )
+        );
+    }
+
+    #[test]
+    fn test5() {
+        assert_eq!(
+            parse("(1+2)*3"),
+            BinOp(
+                Box::new(BinOp(Box::new(Int(1)), Add, Box::new(Int(2)))),
+                Mul,
+                Box::new(Int(3))
+            )
+        );
+        assert!(matches!(
+            ExprParser.parse(lex("1 + #")),
+            Err(pratt::PrattError::UserError(InputError::Unrecognized))
+        ));
+        // A `)` in operand position is a primary which `primary` rejects
+        assert!(matches!(
+            ExprParser.parse(lex("1 + )")),
+            Err(pratt::PrattError::UserError(InputError::Unexpected(
+                Token::RParen
+            )))
+        ));
+    }
+
+    #[test]
+    fn test6() {
+        assert_eq!(parse("2 × 3 ** 2"), parse("2 * 3 ^ 2"));
+        assert_eq!(parse("6 ÷ 2 − 1"), parse("6 / 2 - 1"));
+        assert_eq!(
+            parse("1 ≠ 2"),
+            BinOp(Box::new(Int(1)), Ne, Box::new(Int(2)))
+        );
+        // Spans are byte ranges, and `≠` is three bytes long
+        let error = ExprParser.parse(lex("1 ≠ 2 ≠ 3")).unwrap_err();
+        assert_eq!(error.position(), Some(&(8..11)));
+    }
+}
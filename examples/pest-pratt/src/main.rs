@@ -8,16 +8,19 @@ use pest::Parser;
 #[grammar = "grammar.pest"]
 struct TokenTreeParser;
 
-use pratt::{Affix, Associativity, PrattParser, Precedence, Result};
+use pratt::builder::OperatorTable;
+use pratt::{Affix, Associativity, PrattParser, Precedence, Result, Tail};
 
+// Variables borrow their names from the source code
 #[derive(Debug, Eq, PartialEq)]
-pub enum Expr {
-    BinOp(Box<Expr>, BinOpKind, Box<Expr>),
-    UnOp(UnOpKind, Box<Expr>),
+pub enum Expr<'i> {
+    BinOp(Box<Expr<'i>>, BinOpKind, Box<Expr<'i>>),
+    UnOp(UnOpKind, Box<Expr<'i>>),
     Int(i32),
+    Var(&'i str),
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum BinOpKind {
     Add,
     Sub,
@@ -27,84 +30,119 @@ pub enum BinOpKind {
     Eq,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum UnOpKind {
     Not,
     Neg,
     Try,
 }
 
-struct ExprParser;
+// The node which an operator constructs
+enum OpKind {
+    Bin(BinOpKind),
+    Un(UnOpKind),
+}
+
+struct ExprParser<'i> {
+    operators: OperatorTable<Pair<'i, Rule>, Precedence, OpKind>,
+}
+
+// Matches an operator by its rule and text
+fn op(rule: Rule, text: &'static str) -> impl Fn(&Pair<'_, Rule>) -> bool {
+    move |tree| tree.as_rule() == rule && tree.as_str() == text
+}
+
+impl<'i> ExprParser<'i> {
+    fn new() -> Self {
+        use Affix::{Infix, Postfix, Prefix};
+        use Associativity::*;
+        use BinOpKind::*;
+        use OpKind::*;
+        use UnOpKind::*;
+        let p = Precedence;
+        let operators = OperatorTable::default()
+            .register_with(op(Rule::infix, "="), Infix(p(2), Neither), Bin(Eq))
+            .register_with(op(Rule::infix, "+"), Infix(p(3), Left), Bin(Add))
+            .register_with(op(Rule::infix, "-"), Infix(p(3), Left), Bin(Sub))
+            .register_with(op(Rule::infix, "*"), Infix(p(4), Left), Bin(Mul))
+            .register_with(op(Rule::infix, "/"), Infix(p(4), Left), Bin(Div))
+            .register_with(op(Rule::postfix, "?"), Postfix(p(5)), Un(Try))
+            .register_with(op(Rule::prefix, "-"), Prefix(p(6)), Un(Neg))
+            .register_with(op(Rule::prefix, "!"), Prefix(p(6)), Un(Not))
+            .register_with(op(Rule::infix, "^"), Infix(p(7), Right), Bin(Pow));
+        ExprParser { operators }
+    }
+
+    // The kind of node an operator constructs, as registered in the table
+    fn kind(&self, tree: &Pair<'i, Rule>) -> Result<&OpKind, String> {
+        match self.operators.get(tree) {
+            Some((_, kind)) => Ok(kind),
+            None => Err(format!("Unknown operator {:?}", tree.as_str())),
+        }
+    }
+}
 
-impl<'i, I> PrattParser<I> for ExprParser
+impl<'i, I> PrattParser<I> for ExprParser<'i>
 where
     I: Iterator<Item = Pair<'i, Rule>>,
 {
-    type Error = pratt::NoError;
+    type Error = String;
     type Input = Pair<'i, Rule>;
-    type Output = Expr;
-
-    // Query information about an operator (Affix, Precedence, Associativity)
-    fn query(&mut self, tree: &Self::Input) -> Result<Affix> {
-        let affix = match (tree.as_rule(), tree.as_str()) {
-            (Rule::infix, "=") => Affix::Infix(Precedence(2), Associativity::Neither),
-            (Rule::infix, "+") => Affix::Infix(Precedence(3), Associativity::Left),
-            (Rule::infix, "-") => Affix::Infix(Precedence(3), Associativity::Left),
-            (Rule::infix, "*") => Affix::Infix(Precedence(4), Associativity::Left),
-            (Rule::infix, "/") => Affix::Infix(Precedence(4), Associativity::Left),
-            (Rule::postfix, "?") => Affix::Postfix(Precedence(5)),
-            (Rule::prefix, "-") => Affix::Prefix(Precedence(6)),
-            (Rule::prefix, "!") => Affix::Prefix(Precedence(6)),
-            (Rule::infix, "^") => Affix::Infix(Precedence(7), Associativity::Right),
-            (Rule::group, _) => Affix::Nilfix,
-            (Rule::primary, _) => Affix::Nilfix,
-            (Rule::num, _) => Affix::Nilfix,
-            _ => unreachable!(),
-        };
-        Ok(affix)
+    type Output = Expr<'i>;
+    type Position = std::ops::Range<usize>;
+
+    // Query information about an operator (Affix, Precedence, Associativity),
+    // groups and numbers are primaries
+    fn query(&mut self, tree: &Self::Input) -> Result<Affix, String> {
+        Ok(self.operators.query(tree))
     }
 
-    // Construct a primary expression, e.g. a number
-    fn primary(&mut self, tree: Self::Input) -> Result<Expr> {
+    // Locate an input in the source code, for error messages
+    fn position(&self, tree: &Self::Input) -> Self::Position {
+        let span = tree.as_span();
+        span.start()..span.end()
+    }
+
+    // Construct a primary expression, e.g. a number or a variable
+    fn primary(&mut self, tree: Self::Input, _tail: &mut Tail<'_, I>) -> Result<Expr<'i>, String> {
         let expr = match tree.as_rule() {
-            Rule::num => Expr::Int(tree.as_str().parse().unwrap()),
-            Rule::group => self.parse(&mut tree.into_inner()).unwrap(),
-            _ => unreachable!(),
+            Rule::num => Expr::Int(tree.as_str().parse().map_err(|e| format!("{}", e))?),
+            Rule::var => Expr::Var(tree.as_str()),
+            Rule::group => self
+                .parse(&mut tree.into_inner())
+                .map_err(|error| error.to_string())?,
+            _ => return Err(format!("Unhandled primary {:?}", tree.as_str())),
         };
         Ok(expr)
     }
 
     // Construct a binary infix expression, e.g. 1+1
-    fn infix(&mut self, lhs: Expr, tree: Self::Input, rhs: Expr) -> Result<Expr> {
-        let op = match tree.as_str() {
-            "+" => BinOpKind::Add,
-            "-" => BinOpKind::Sub,
-            "*" => BinOpKind::Mul,
-            "/" => BinOpKind::Div,
-            "^" => BinOpKind::Pow,
-            "=" => BinOpKind::Eq,
-            _ => unreachable!(),
-        };
-        Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+    fn infix(
+        &mut self,
+        lhs: Expr<'i>,
+        tree: Self::Input,
+        rhs: Expr<'i>,
+    ) -> Result<Expr<'i>, String> {
+        match *self.kind(&tree)? {
+            OpKind::Bin(op) => Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs))),
+            OpKind::Un(_) => Err(format!("Unhandled infix operator {:?}", tree.as_str())),
+        }
     }
 
     // Construct a unary prefix expression, e.g. !1
-    fn prefix(&mut self, tree: Self::Input, rhs: Expr) -> Result<Expr> {
-        let op = match tree.as_str() {
-            "!" => UnOpKind::Not,
-            "-" => UnOpKind::Neg,
-            _ => unreachable!(),
-        };
-        Ok(Expr::UnOp(op, Box::new(rhs)))
+    fn prefix(&mut self, tree: Self::Input, rhs: Expr<'i>) -> Result<Expr<'i>, String> {
+        match *self.kind(&tree)? {
+            OpKind::Un(op) => Ok(Expr::UnOp(op, Box::new(rhs))),
+            OpKind::Bin(_) => Err(format!("Unhandled prefix operator {:?}", tree.as_str())),
+        }
     }
 
     // Construct a unary postfix expression, e.g. 1?
-    fn postfix(&mut self, lhs: Expr, tree: Self::Input) -> Result<Expr> {
-        let op = match tree.as_str() {
-            "?" => UnOpKind::Try,
-            _ => unreachable!(),
-        };
-        Ok(Expr::UnOp(op, Box::new(lhs)))
+    fn postfix(&mut self, lhs: Expr<'i>, tree: Self::Input) -> Result<Expr<'i>, String> {
+        match *self.kind(&tree)? {
+            OpKind::Un(op) => Ok(Expr::UnOp(op, Box::new(lhs))),
+            OpKind::Bin(_) => Err(format!("Unhandled postfix operator {:?}", tree.as_str())),
+        }
     }
 }
 
@@ -118,17 +156,15 @@ fn main() {
     let tt = TokenTreeParser::parse(Rule::group, &input).unwrap_or_else(|e| panic!("{}", e));
     println!("TokenTree: {:?}", tt);
 
-    let expr = ExprParser.parse(tt.into_iter()).unwrap();
+    let expr = ExprParser::new().parse(tt).unwrap();
     println!("Expression: {:?}", expr);
 }
 
 #[cfg(test)]
 mod test {
-    fn parse(input: &str) -> Expr {
-        let tt = TokenTreeParser::parse(Rule::group, &input)
-            .unwrap()
-            .into_iter();
-        ExprParser.parse(tt.into_iter()).unwrap()
+    fn parse(input: &str) -> Expr<'_> {
+        let tt = TokenTreeParser::parse(Rule::group, input).unwrap();
+        ExprParser::new().parse(tt).unwrap()
     }
     use super::BinOpKind::*;
     use super::Expr::*;
@@ -137,10 +173,12 @@ mod test {
 
     #[test]
     fn test1() {
-        assert_eq!(
-            parse("1=2=3"),
-            BinOp(Box::new(Int(1)), Eq, Box::new(Int(2)))
-        );
+        let mut tt = TokenTreeParser::parse(Rule::group, "1=2=3").unwrap();
+        let group = tt.next().unwrap().into_inner();
+        assert!(matches!(
+            ExprParser::new().parse(group),
+            Err(pratt::PrattError::NonAssociative(..))
+        ));
     }
 
     #[test]
@@ -196,4 +234,38 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn test5() {
+        let input = String::from("x1 + 2 * y");
+        let expr = parse(&input);
+        assert_eq!(
+            expr,
+            BinOp(
+                Box::new(Var("x1")),
+                Add,
+                Box::new(BinOp(Box::new(Int(2)), Mul, Box::new(Var("y"))))
+            )
+        );
+        // The name is a slice of the input, not a copy
+        match expr {
+            BinOp(lhs, _, _) => assert!(matches!(*lhs, Var(x) if std::ptr::eq(x, &input[..2]))),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn mismatched_operator() {
+        // `?` is classified as postfix, but constructs a binary node
+        let operators = OperatorTable::default().register_with(
+            op(Rule::postfix, "?"),
+            Affix::Postfix(Precedence(5)),
+            OpKind::Bin(Add),
+        );
+        let tt = TokenTreeParser::parse(Rule::group, "1?").unwrap();
+        assert!(matches!(
+            ExprParser { operators }.parse(tt),
+            Err(pratt::PrattError::UserError(error)) if error == "Unhandled postfix operator \"?\""
+        ));
+    }
 }
@@ -0,0 +1,201 @@
+//! Derives the operator table of a [`pratt`] parser from attributes on the
+//! variants of its input type.
+//!
+//! [`pratt`]: https://docs.rs/pratt
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Ident, LitInt, Token};
+
+const AFFIXES: &[&str] = &[
+    "nilfix",
+    "infix",
+    "infix_chain",
+    "prefix",
+    "prefix_primary",
+    "postfix",
+    "postfix_with",
+    "postfix_or_infix",
+    "ternary",
+    "circumfix",
+    "mixfix",
+];
+
+/// Implements `fn affix(&self) -> pratt::Affix` for an enum, so that
+/// `PrattParser::query` can be written as `Ok(input.affix())`.
+///
+/// Each variant takes at most one of the following attributes, where
+/// precedences are integer literals and associativities are one of `left`,
+/// `right` or `neither`. Variants without one are `Affix::Nilfix`.
+///
+/// - `#[nilfix]`
+/// - `#[infix(associativity, precedence)]`
+/// - `#[infix_chain(precedence)]`
+/// - `#[prefix(precedence)]`
+/// - `#[prefix_primary(precedence)]`
+/// - `#[postfix(precedence)]`
+/// - `#[postfix_with(precedence)]`
+/// - `#[postfix_or_infix(associativity, precedence)]`
+/// - `#[ternary(associativity, precedence)]`
+/// - `#[circumfix]`
+/// - `#[mixfix(precedence, separators)]`
+///
+/// ```
+/// use pratt::{Affix, Associativity, Precedence};
+/// use pratt_derive::Operator;
+///
+/// #[derive(Operator)]
+/// enum Token {
+///     #[infix(left, 3)]
+///     Add,
+///     #[prefix(6)]
+///     Not,
+///     Int(i64),
+/// }
+///
+/// assert!(matches!(
+///     Token::Add.affix(),
+///     Affix::Infix(Precedence(3), Associativity::Left)
+/// ));
+/// assert!(matches!(Token::Int(1).affix(), Affix::Nilfix));
+/// ```
+///
+/// A variant with two affixes is rejected:
+///
+/// ```compile_fail
+/// use pratt_derive::Operator;
+///
+/// #[derive(Operator)]
+/// enum Token {
+///     #[infix(left, 3)]
+///     #[prefix(6)]
+///     Sub,
+/// }
+/// ```
+#[proc_macro_derive(
+    Operator,
+    attributes(
+        nilfix,
+        infix,
+        infix_chain,
+        prefix,
+        prefix_primary,
+        postfix,
+        postfix_with,
+        postfix_or_infix,
+        ternary,
+        circumfix,
+        mixfix
+    )
+)]
+pub fn derive_operator(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                input.ident,
+                "`Operator` can only be derived for enums",
+            ))
+        }
+    };
+    let mut arms = Vec::new();
+    for variant in data.variants {
+        let mut affixes = variant
+            .attrs
+            .iter()
+            .filter(|attr| AFFIXES.iter().any(|name| attr.path().is_ident(name)));
+        let affix = match (affixes.next(), affixes.next()) {
+            (None, _) => quote!(::pratt::Affix::Nilfix),
+            (Some(attr), None) => affix(attr)?,
+            (Some(_), Some(attr)) => {
+                return Err(Error::new_spanned(
+                    attr,
+                    "a variant can only be classified as one affix",
+                ))
+            }
+        };
+        let ident = variant.ident;
+        arms.push(quote!(Self::#ident { .. } => #affix,));
+    }
+    let ident = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// The affix of this input, as declared by its attributes.
+            pub fn affix(&self) -> ::pratt::Affix {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// Expands a single affix attribute into an `Affix` expression.
+fn affix(attr: &Attribute) -> syn::Result<TokenStream> {
+    let name = attr.path().get_ident().unwrap().to_string();
+    match name.as_str() {
+        "nilfix" | "circumfix" => {
+            attr.meta.require_path_only()?;
+            Ok(match name.as_str() {
+                "nilfix" => quote!(::pratt::Affix::Nilfix),
+                _ => quote!(::pratt::Affix::Circumfix),
+            })
+        }
+        "infix" | "postfix_or_infix" | "ternary" => attr.parse_args_with(|input: ParseStream| {
+            let associativity = associativity(&input.parse()?)?;
+            input.parse::<Token![,]>()?;
+            let precedence = precedence(&input.parse()?)?;
+            Ok(match name.as_str() {
+                "infix" => quote!(::pratt::Affix::Infix(#precedence, #associativity)),
+                "postfix_or_infix" => {
+                    quote!(::pratt::Affix::PostfixOrInfix(#precedence, #associativity))
+                }
+                _ => quote!(::pratt::Affix::Ternary(#precedence, #associativity)),
+            })
+        }),
+        "mixfix" => attr.parse_args_with(|input: ParseStream| {
+            let precedence = precedence(&input.parse()?)?;
+            input.parse::<Token![,]>()?;
+            let separators = input.parse::<LitInt>()?.base10_parse::<usize>()?;
+            Ok(quote!(::pratt::Affix::Mixfix(#precedence, #separators)))
+        }),
+        _ => {
+            let precedence = precedence(&attr.parse_args()?)?;
+            let variant = match name.as_str() {
+                "infix_chain" => "InfixChain",
+                "prefix" => "Prefix",
+                "prefix_primary" => "PrefixPrimary",
+                "postfix" => "Postfix",
+                _ => "PostfixWith",
+            };
+            let variant = Ident::new(variant, Span::call_site());
+            Ok(quote!(::pratt::Affix::#variant(#precedence)))
+        }
+    }
+}
+
+fn precedence(lit: &LitInt) -> syn::Result<TokenStream> {
+    let precedence = lit.base10_parse::<u32>()?;
+    Ok(quote!(::pratt::Precedence(#precedence)))
+}
+
+fn associativity(ident: &Ident) -> syn::Result<TokenStream> {
+    match ident.to_string().as_str() {
+        "left" => Ok(quote!(::pratt::Associativity::Left)),
+        "right" => Ok(quote!(::pratt::Associativity::Right)),
+        "neither" => Ok(quote!(::pratt::Associativity::Neither)),
+        _ => Err(Error::new_spanned(
+            ident,
+            "expected `left`, `right` or `neither`",
+        )),
+    }
+}
@@ -0,0 +1,122 @@
+use pratt::{Affix, Associativity, NoError, PrattParser, Precedence, Result, Tail};
+use pratt_derive::Operator;
+
+#[derive(Debug, Operator)]
+enum Token {
+    #[infix(neither, 2)]
+    Eq,
+    #[infix(left, 3)]
+    Add,
+    #[infix_chain(3)]
+    And,
+    #[infix(right, 7)]
+    Pow,
+    #[prefix(6)]
+    Not,
+    #[prefix_primary(6)]
+    Typeof,
+    #[postfix(5)]
+    Try,
+    #[postfix_with(8)]
+    Index,
+    #[postfix_or_infix(left, 4)]
+    Star,
+    #[ternary(right, 1)]
+    Question,
+    #[circumfix]
+    LParen,
+    #[mixfix(1, 2)]
+    If,
+    #[nilfix]
+    Colon,
+    Int(i64),
+    Var {
+        index: i64,
+    },
+}
+
+#[test]
+fn affixes() {
+    assert!(matches!(
+        Token::Eq.affix(),
+        Affix::Infix(Precedence(2), Associativity::Neither)
+    ));
+    assert!(matches!(
+        Token::Add.affix(),
+        Affix::Infix(Precedence(3), Associativity::Left)
+    ));
+    assert!(matches!(
+        Token::And.affix(),
+        Affix::InfixChain(Precedence(3))
+    ));
+    assert!(matches!(
+        Token::Pow.affix(),
+        Affix::Infix(Precedence(7), Associativity::Right)
+    ));
+    assert!(matches!(Token::Not.affix(), Affix::Prefix(Precedence(6))));
+    assert!(matches!(
+        Token::Typeof.affix(),
+        Affix::PrefixPrimary(Precedence(6))
+    ));
+    assert!(matches!(Token::Try.affix(), Affix::Postfix(Precedence(5))));
+    assert!(matches!(
+        Token::Index.affix(),
+        Affix::PostfixWith(Precedence(8))
+    ));
+    assert!(matches!(
+        Token::Star.affix(),
+        Affix::PostfixOrInfix(Precedence(4), Associativity::Left)
+    ));
+    assert!(matches!(
+        Token::Question.affix(),
+        Affix::Ternary(Precedence(1), Associativity::Right)
+    ));
+    assert!(matches!(Token::LParen.affix(), Affix::Circumfix));
+    assert!(matches!(Token::If.affix(), Affix::Mixfix(Precedence(1), 2)));
+    assert!(matches!(Token::Colon.affix(), Affix::Nilfix));
+    assert!(matches!(Token::Int(1).affix(), Affix::Nilfix));
+    assert!(matches!(Token::Var { index: 0 }.affix(), Affix::Nilfix));
+}
+
+struct Calculator;
+
+impl<I: Iterator<Item = Token>> PrattParser<I> for Calculator {
+    type Error = NoError;
+    type Input = Token;
+    type Output = i64;
+    type Position = ();
+
+    fn query(&mut self, token: &Token) -> Result<Affix> {
+        Ok(token.affix())
+    }
+
+    fn primary(&mut self, token: Token, _: &mut Tail<'_, I>) -> Result<i64> {
+        match token {
+            Token::Int(i) | Token::Var { index: i } => Ok(i),
+            _ => Err(NoError),
+        }
+    }
+
+    fn infix(&mut self, lhs: i64, token: Token, rhs: i64) -> Result<i64> {
+        match token {
+            Token::Add => Ok(lhs + rhs),
+            Token::Pow => Ok(lhs.pow(rhs as u32)),
+            _ => Err(NoError),
+        }
+    }
+
+    fn prefix(&mut self, _: Token, rhs: i64) -> Result<i64> {
+        Ok(-rhs)
+    }
+
+    fn postfix(&mut self, lhs: i64, _: Token) -> Result<i64> {
+        Ok(lhs)
+    }
+}
+
+#[test]
+fn query() {
+    use Token::*;
+    let tokens = [Not, Int(2), Pow, Int(2), Add, Int(1)];
+    assert_eq!(Calculator.parse(tokens.into_iter()).unwrap(), -3);
+}
@@ -0,0 +1,242 @@
+//! Parsers built from a table of operators instead of a hand-written
+//! [`PrattParser`] implementation, and tables which only replace
+//! [`PrattParser::query`].
+
+use crate::{Affix, Associativity, BindingPower, NoError, PrattParser, Precedence, Tail};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+enum Operator<O> {
+    Infix(Precedence, Associativity, Box<dyn Fn(O, O) -> O>),
+    Prefix(Precedence, Box<dyn Fn(O) -> O>),
+    Postfix(Precedence, Box<dyn Fn(O) -> O>),
+}
+
+/// Collects the operators of a grammar, see [`PrattBuilder::build`].
+///
+/// Inputs are told apart by a key, which is computed from each input by a
+/// user-supplied function. Inputs whose key is not registered as an operator
+/// are primaries.
+pub struct PrattBuilder<I, O, K> {
+    key: Box<dyn Fn(&I) -> K>,
+    operators: Vec<(K, Operator<O>)>,
+    primary: Option<Box<dyn Fn(I) -> O>>,
+}
+
+impl<I, O, K: PartialEq> PrattBuilder<I, O, K> {
+    pub fn new(key: impl Fn(&I) -> K + 'static) -> Self {
+        PrattBuilder {
+            key: Box::new(key),
+            operators: Vec::new(),
+            primary: None,
+        }
+    }
+
+    pub fn infix(
+        mut self,
+        key: K,
+        precedence: Precedence,
+        associativity: Associativity,
+        f: impl Fn(O, O) -> O + 'static,
+    ) -> Self {
+        let operator = Operator::Infix(precedence, associativity, Box::new(f));
+        self.operators.push((key, operator));
+        self
+    }
+
+    pub fn prefix(mut self, key: K, precedence: Precedence, f: impl Fn(O) -> O + 'static) -> Self {
+        let operator = Operator::Prefix(precedence, Box::new(f));
+        self.operators.push((key, operator));
+        self
+    }
+
+    pub fn postfix(mut self, key: K, precedence: Precedence, f: impl Fn(O) -> O + 'static) -> Self {
+        let operator = Operator::Postfix(precedence, Box::new(f));
+        self.operators.push((key, operator));
+        self
+    }
+
+    /// Sets the constructor of primary expressions.
+    pub fn primary(mut self, f: impl Fn(I) -> O + 'static) -> Self {
+        self.primary = Some(Box::new(f));
+        self
+    }
+
+    /// Returns a parser for the registered operators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no constructor of primary expressions has been set.
+    pub fn build(self) -> TableParser<I, O, K> {
+        TableParser {
+            key: self.key,
+            operators: self.operators,
+            primary: self.primary.expect("a primary constructor is required"),
+        }
+    }
+}
+
+/// A parser built by a [`PrattBuilder`].
+pub struct TableParser<I, O, K> {
+    key: Box<dyn Fn(&I) -> K>,
+    operators: Vec<(K, Operator<O>)>,
+    primary: Box<dyn Fn(I) -> O>,
+}
+
+impl<I, O, K: PartialEq> TableParser<I, O, K> {
+    fn operator(&self, input: &I) -> Option<&Operator<O>> {
+        let key = (self.key)(input);
+        self.operators
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, operator)| operator)
+    }
+}
+
+impl<I, O, K, Inputs> PrattParser<Inputs> for TableParser<I, O, K>
+where
+    I: core::fmt::Debug,
+    K: PartialEq,
+    Inputs: Iterator<Item = I>,
+{
+    type Error = NoError;
+    type Input = I;
+    type Output = O;
+    type Position = ();
+
+    fn query(&mut self, input: &I) -> Result<Affix, NoError> {
+        let affix = match self.operator(input) {
+            Some(Operator::Infix(precedence, associativity, _)) => {
+                Affix::Infix(*precedence, *associativity)
+            }
+            Some(Operator::Prefix(precedence, _)) => Affix::Prefix(*precedence),
+            Some(Operator::Postfix(precedence, _)) => Affix::Postfix(*precedence),
+            None => Affix::Nilfix,
+        };
+        Ok(affix)
+    }
+
+    fn primary(&mut self, input: I, _: &mut Tail<'_, Inputs>) -> Result<O, NoError> {
+        Ok((self.primary)(input))
+    }
+
+    fn infix(&mut self, lhs: O, op: I, rhs: O) -> Result<O, NoError> {
+        match self.operator(&op) {
+            Some(Operator::Infix(_, _, f)) => Ok(f(lhs, rhs)),
+            // Only if the key of `op` changed since it was queried
+            _ => Err(NoError),
+        }
+    }
+
+    fn prefix(&mut self, op: I, rhs: O) -> Result<O, NoError> {
+        match self.operator(&op) {
+            Some(Operator::Prefix(_, f)) => Ok(f(rhs)),
+            _ => Err(NoError),
+        }
+    }
+
+    fn postfix(&mut self, lhs: O, op: I) -> Result<O, NoError> {
+        match self.operator(&op) {
+            Some(Operator::Postfix(_, f)) => Ok(f(lhs)),
+            _ => Err(NoError),
+        }
+    }
+}
+
+/// Classifies inputs by the first registered predicate which matches them,
+/// for use in [`PrattParser::query`].
+///
+/// Each operator may also carry data of type `D`, e.g. the kind of node it
+/// constructs, which the callbacks of the parser can look up with
+/// [`OperatorTable::get`] instead of matching on the input again.
+pub struct OperatorTable<I, P = Precedence, D = ()> {
+    operators: Vec<(Box<dyn Fn(&I) -> bool>, Affix<P>, D)>,
+}
+
+impl<I, P: BindingPower, D> Default for OperatorTable<I, P, D> {
+    fn default() -> Self {
+        OperatorTable {
+            operators: Vec::new(),
+        }
+    }
+}
+
+impl<I, P: BindingPower> OperatorTable<I, P> {
+    pub fn new() -> Self {
+        OperatorTable::default()
+    }
+
+    /// Registers inputs matching `matches` as `affix`.
+    pub fn register(self, matches: impl Fn(&I) -> bool + 'static, affix: Affix<P>) -> Self {
+        self.register_with(matches, affix, ())
+    }
+
+    pub fn infix_left(self, matches: impl Fn(&I) -> bool + 'static, precedence: P) -> Self {
+        self.register(matches, Affix::Infix(precedence, Associativity::Left))
+    }
+
+    pub fn infix_right(self, matches: impl Fn(&I) -> bool + 'static, precedence: P) -> Self {
+        self.register(matches, Affix::Infix(precedence, Associativity::Right))
+    }
+
+    pub fn infix_neither(self, matches: impl Fn(&I) -> bool + 'static, precedence: P) -> Self {
+        self.register(matches, Affix::Infix(precedence, Associativity::Neither))
+    }
+
+    pub fn prefix(self, matches: impl Fn(&I) -> bool + 'static, precedence: P) -> Self {
+        self.register(matches, Affix::Prefix(precedence))
+    }
+
+    pub fn postfix(self, matches: impl Fn(&I) -> bool + 'static, precedence: P) -> Self {
+        self.register(matches, Affix::Postfix(precedence))
+    }
+}
+
+impl<I, P: BindingPower, D> OperatorTable<I, P, D> {
+    /// Registers inputs matching `matches` as `affix`, carrying `data`.
+    pub fn register_with(
+        mut self,
+        matches: impl Fn(&I) -> bool + 'static,
+        affix: Affix<P>,
+        data: D,
+    ) -> Self {
+        self.operators.push((Box::new(matches), affix, data));
+        self
+    }
+
+    /// Returns the affix and data of the first operator matching `input`,
+    /// or `None` if there is none.
+    pub fn get(&self, input: &I) -> Option<(Affix<P>, &D)> {
+        self.operators
+            .iter()
+            .find(|(matches, _, _)| matches(input))
+            .map(|(_, affix, data)| (*affix, data))
+    }
+
+    /// Returns the affix of the first operator matching `input`, or
+    /// `Affix::Nilfix` if there is none.
+    pub fn query(&self, input: &I) -> Affix<P> {
+        self.get(input).map_or(Affix::Nilfix, |(affix, _)| affix)
+    }
+
+    /// Checks that operators with operands on both sides which share a
+    /// precedence also share an associativity, see
+    /// [`PrattParser::check_associativity`]. Returns the data of the first
+    /// two operators which do not, in the order they were registered.
+    pub fn validate(&self) -> Result<(), (&D, &D)> {
+        let binary = || {
+            self.operators
+                .iter()
+                .filter_map(|(_, affix, data)| Some((affix.binary()?, data)))
+        };
+        for (i, ((precedence, associativity), data)) in binary().enumerate() {
+            let conflict = binary()
+                .take(i)
+                .find(|&((p, a), _)| p == precedence && a != associativity);
+            if let Some((_, first)) = conflict {
+                return Err((first, data));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+//! A parser which records every error it recovers from, see
+//! [`PrattParser::parse_all_errors`].
+
+use crate::{Affix, BindingPower, MixfixPart, PrattError, PrattParser, QueryContext, Tail};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+/// Forwards to `parser`, but keeps a copy of each error passed to `recover`
+/// for which `parser` returned a placeholder node.
+pub(crate) struct Collect<'a, T, Inputs, P>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    pub(crate) parser: &'a mut T,
+    pub(crate) errors: Vec<PrattError<T::Input, T::Error, T::Position>>,
+    marker: PhantomData<fn(Inputs, P)>,
+}
+
+impl<'a, T, Inputs, P> Collect<'a, T, Inputs, P>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    pub(crate) fn new(parser: &'a mut T) -> Self {
+        Collect {
+            parser,
+            errors: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Inputs, P> PrattParser<Inputs, P> for Collect<'_, T, Inputs, P>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    T::Input: Clone,
+    T::Error: Clone,
+    T::Position: Clone,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    type Error = T::Error;
+    type Input = T::Input;
+    type Output = T::Output;
+    type Position = T::Position;
+
+    fn query(&mut self, input: &T::Input) -> Result<Affix<P>, T::Error> {
+        self.parser.query(input)
+    }
+
+    fn classify(&mut self, input: &T::Input) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.classify(input)
+    }
+
+    fn query_led(&mut self, input: &T::Input) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.query_led(input)
+    }
+
+    fn query_with_context(
+        &mut self,
+        context: &mut QueryContext<'_, Inputs, P>,
+    ) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.query_with_context(context)
+    }
+
+    fn primary(
+        &mut self,
+        input: T::Input,
+        tail: &mut Tail<'_, Inputs, P>,
+    ) -> Result<T::Output, T::Error> {
+        self.parser.primary(input, tail)
+    }
+
+    fn infix(
+        &mut self,
+        lhs: T::Output,
+        op: T::Input,
+        rhs: T::Output,
+    ) -> Result<T::Output, T::Error> {
+        self.parser.infix(lhs, op, rhs)
+    }
+
+    fn prefix(&mut self, op: T::Input, rhs: T::Output) -> Result<T::Output, T::Error> {
+        self.parser.prefix(op, rhs)
+    }
+
+    fn infix_chain(
+        &mut self,
+        head: T::Output,
+        rest: Vec<(T::Input, T::Output)>,
+    ) -> Result<T::Output, T::Error> {
+        self.parser.infix_chain(head, rest)
+    }
+
+    fn postfix(&mut self, lhs: T::Output, op: T::Input) -> Result<T::Output, T::Error> {
+        self.parser.postfix(lhs, op)
+    }
+
+    fn postfix_with(
+        &mut self,
+        lhs: T::Output,
+        op: T::Input,
+        tail: &mut Tail<'_, Inputs, P>,
+    ) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>> {
+        self.parser.postfix_with(lhs, op, tail)
+    }
+
+    fn ternary(
+        &mut self,
+        cond: T::Output,
+        op: T::Input,
+        then: T::Output,
+        delimiter: T::Input,
+        els: T::Output,
+    ) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>> {
+        self.parser.ternary(cond, op, then, delimiter, els)
+    }
+
+    fn circumfix(
+        &mut self,
+        open: T::Input,
+        inner: T::Output,
+        close: T::Input,
+    ) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>> {
+        self.parser.circumfix(open, inner, close)
+    }
+
+    fn mixfix(
+        &mut self,
+        parts: Vec<MixfixPart<T::Input, T::Output>>,
+    ) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>> {
+        self.parser.mixfix(parts)
+    }
+
+    fn juxtaposition(&self) -> Option<P> {
+        self.parser.juxtaposition()
+    }
+
+    fn juxtapose(
+        &mut self,
+        lhs: T::Output,
+        rhs: T::Output,
+    ) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>> {
+        self.parser.juxtapose(lhs, rhs)
+    }
+
+    fn closes(op: &T::Input, delimiter: &T::Input) -> bool {
+        T::closes(op, delimiter)
+    }
+
+    fn on_empty(&mut self) -> Result<Option<T::Output>, T::Error> {
+        self.parser.on_empty()
+    }
+
+    fn recover(
+        &mut self,
+        error: &PrattError<T::Input, T::Error, T::Position>,
+        tail: &mut Tail<'_, Inputs, P>,
+    ) -> Option<T::Output> {
+        let node = self.parser.recover(error, tail)?;
+        self.errors.push(error.clone());
+        Some(node)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.parser.max_depth()
+    }
+
+    fn position(&self, input: &T::Input) -> T::Position {
+        self.parser.position(input)
+    }
+
+    fn normalize(&self, precedence: P) -> P {
+        self.parser.normalize(precedence)
+    }
+}
@@ -0,0 +1,75 @@
+//! Parsing a stream of expressions lazily, see [`PrattParser::expressions`].
+
+use crate::{BindingPower, PrattError, PrattParser};
+use core::iter::Peekable;
+use core::marker::PhantomData;
+
+/// An iterator over the expressions of a stream of inputs, each ended by a
+/// terminator, which parses one expression per call to `next`.
+///
+/// Iteration ends when the inputs run out, or after the first error, since
+/// the state of the inputs is unspecified after a failed parse. An input
+/// other than the terminator after an expression is such an error, a
+/// `MissingTerminator` as in [`PrattParser::parse_terminated`].
+pub struct PrattIter<'a, T: ?Sized, Inputs: Iterator, P, F> {
+    parser: &'a mut T,
+    inputs: Peekable<Inputs>,
+    terminator: F,
+    failed: bool,
+    marker: PhantomData<fn(P)>,
+}
+
+impl<'a, T, Inputs, P, F> PrattIter<'a, T, Inputs, P, F>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+    F: FnMut(&T::Input) -> bool,
+{
+    pub(crate) fn new(parser: &'a mut T, inputs: Inputs, terminator: F) -> Self {
+        PrattIter {
+            parser,
+            inputs: inputs.peekable(),
+            terminator,
+            failed: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// The inputs which have not been parsed yet.
+    pub fn inputs(&mut self) -> &mut Peekable<Inputs> {
+        &mut self.inputs
+    }
+}
+
+impl<T, Inputs, P, F> Iterator for PrattIter<'_, T, Inputs, P, F>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+    F: FnMut(&T::Input) -> bool,
+{
+    type Item = Result<T::Output, PrattError<T::Input, T::Error, T::Position>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        self.inputs.peek()?;
+        let expr = self
+            .parser
+            .parse_until(&mut self.inputs, &mut self.terminator);
+        if expr.is_err() {
+            self.failed = true;
+            return Some(expr);
+        }
+        match self.inputs.next() {
+            Some(input) if !(self.terminator)(&input) => {
+                self.failed = true;
+                let position = self.parser.position(&input);
+                Some(Err(PrattError::MissingTerminator(Some((input, position)))))
+            }
+            _ => Some(expr),
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//! A parse loop which keeps pending operators on the heap instead of the
+//! call stack, see [`PrattParser::parse_iterative`].
+
+use crate::{
+    empty, follows, lookup, missing_operand, recover, Affix, Associativity, BindingPower,
+    Denotation, MixfixPart, PrattError, PrattParser, Tail,
+};
+use alloc::vec::Vec;
+
+/// An operator waiting for the operand which is currently being parsed,
+/// together with the binding powers of the parse it was found in.
+enum Frame<I, O, P> {
+    Prefix {
+        op: I,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Circumfix {
+        open: I,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Infix {
+        lhs: O,
+        op: I,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Juxtapose {
+        lhs: O,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Chain {
+        head: O,
+        rest: Vec<(I, O)>,
+        op: I,
+        precedence: P,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Mixfix {
+        parts: Vec<MixfixPart<I, O>>,
+        keyword: I,
+        remaining: usize,
+        precedence: P,
+        rbp: P,
+        last: Affix<P>,
+    },
+    Then {
+        cond: O,
+        op: I,
+        rbp: P,
+        last: Affix<P>,
+        els: P,
+    },
+    Else {
+        cond: O,
+        op: I,
+        then: O,
+        delimiter: I,
+        rbp: P,
+        last: Affix<P>,
+    },
+}
+
+impl<I, O, P> Frame<I, O, P> {
+    /// Returns the opening input if the operand is delimited by this frame.
+    fn opener(&self) -> Option<&I> {
+        match self {
+            Frame::Circumfix { open, .. } => Some(open),
+            Frame::Then { op, .. } => Some(op),
+            Frame::Mixfix {
+                keyword, remaining, ..
+            } if *remaining > 0 => Some(keyword),
+            _ => None,
+        }
+    }
+
+    /// Lends the input in front of the operand to `f`, e.g. to report the
+    /// operand as missing, and puts back the input which `f` returns.
+    fn with_op<R, E>(self, f: impl FnOnce(I) -> Result<(I, R), E>) -> Result<(Self, R), E> {
+        Ok(match self {
+            Frame::Prefix { op, rbp, last } => {
+                let (op, r) = f(op)?;
+                (Frame::Prefix { op, rbp, last }, r)
+            }
+            Frame::Circumfix { open, rbp, last } => {
+                let (open, r) = f(open)?;
+                (Frame::Circumfix { open, rbp, last }, r)
+            }
+            Frame::Infix { lhs, op, rbp, last } => {
+                let (op, r) = f(op)?;
+                (Frame::Infix { lhs, op, rbp, last }, r)
+            }
+            Frame::Juxtapose { .. } => unreachable!("juxtaposed operands are never missing"),
+            Frame::Chain {
+                head,
+                rest,
+                op,
+                precedence,
+                rbp,
+                last,
+            } => {
+                let (op, r) = f(op)?;
+                let frame = Frame::Chain {
+                    head,
+                    rest,
+                    op,
+                    precedence,
+                    rbp,
+                    last,
+                };
+                (frame, r)
+            }
+            Frame::Mixfix {
+                parts,
+                keyword,
+                remaining,
+                precedence,
+                rbp,
+                last,
+            } => {
+                let (keyword, r) = f(keyword)?;
+                let frame = Frame::Mixfix {
+                    parts,
+                    keyword,
+                    remaining,
+                    precedence,
+                    rbp,
+                    last,
+                };
+                (frame, r)
+            }
+            Frame::Then {
+                cond,
+                op,
+                rbp,
+                last,
+                els,
+            } => {
+                let (op, r) = f(op)?;
+                let frame = Frame::Then {
+                    cond,
+                    op,
+                    rbp,
+                    last,
+                    els,
+                };
+                (frame, r)
+            }
+            Frame::Else {
+                cond,
+                op,
+                then,
+                delimiter,
+                rbp,
+                last,
+            } => {
+                let (delimiter, r) = f(delimiter)?;
+                let frame = Frame::Else {
+                    cond,
+                    op,
+                    then,
+                    delimiter,
+                    rbp,
+                    last,
+                };
+                (frame, r)
+            }
+        })
+    }
+}
+
+pub(crate) fn parse<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+) -> Result<T::Output, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let mut stack: Vec<Frame<T::Input, T::Output, P>> = Vec::new();
+    // Indices into `stack` of the frames which delimit an operand
+    let mut scopes: Vec<usize> = Vec::new();
+    let mut rbp = P::MIN;
+    let max_depth = parser.max_depth();
+    'nud: loop {
+        // Every frame stands for one operand nesting, like `descend`
+        if max_depth.is_some_and(|max| tail.depth + stack.len() > max) {
+            return Err(PrattError::DepthExceeded);
+        }
+        // Operands are ended by the innermost delimiter, like `Tail::until`
+        let opener = scopes.last().and_then(|i| stack[*i].opener());
+        let info = next_affix(parser, tail, opener, Denotation::Nud, rbp)?;
+        // The operator which ends the operand, or `None` if it cannot be extended
+        let (mut node, mut last) = match info {
+            None => match stack.pop() {
+                Some(frame) => {
+                    let (frame, node) = frame.with_op(|op| missing_operand(parser, op, tail))?;
+                    stack.push(frame);
+                    (node, None)
+                }
+                None => (empty(parser, tail)?, None),
+            },
+            Some(info) => {
+                let head = tail.next_input().unwrap();
+                let last = info;
+                let node = match info {
+                    Affix::Prefix(precedence) => {
+                        stack.push(Frame::Prefix {
+                            op: head,
+                            rbp,
+                            last,
+                        });
+                        rbp = parser.normalize(precedence).lower();
+                        continue 'nud;
+                    }
+                    Affix::PrefixPrimary(precedence) => {
+                        let inner = parser.normalize(precedence).lower();
+                        let chained = next_affix(parser, tail, opener, Denotation::Nud, inner)?;
+                        if let Some(Affix::Prefix(_) | Affix::PrefixPrimary(_)) = chained {
+                            let next = tail.next_input().unwrap();
+                            let position = parser.position(&next);
+                            recover(parser, PrattError::UnexpectedPrefix(next, position), tail)?
+                        } else {
+                            stack.push(Frame::Prefix {
+                                op: head,
+                                rbp,
+                                last,
+                            });
+                            rbp = inner;
+                            continue 'nud;
+                        }
+                    }
+                    Affix::Circumfix => {
+                        scopes.push(stack.len());
+                        stack.push(Frame::Circumfix {
+                            open: head,
+                            rbp,
+                            last,
+                        });
+                        rbp = P::MIN;
+                        continue 'nud;
+                    }
+                    Affix::Mixfix(precedence, remaining) => {
+                        if remaining > 0 {
+                            scopes.push(stack.len());
+                        }
+                        stack.push(Frame::Mixfix {
+                            parts: Vec::new(),
+                            keyword: head,
+                            remaining,
+                            precedence,
+                            rbp,
+                            last,
+                        });
+                        rbp = match remaining {
+                            0 => parser.normalize(precedence).lower(),
+                            _ => P::MIN,
+                        };
+                        continue 'nud;
+                    }
+                    Affix::Nilfix => match opener {
+                        Some(opener) => {
+                            let mut closes = |input: &T::Input| T::closes(opener, input);
+                            parser.primary(head, &mut tail.until(&mut closes))
+                        }
+                        None => parser.primary(head, tail),
+                    }
+                    .map_err(PrattError::UserError)?,
+                    Affix::Postfix(_) | Affix::PostfixWith(_) => {
+                        let position = parser.position(&head);
+                        recover(parser, PrattError::UnexpectedPostfix(head, position), tail)?
+                    }
+                    Affix::Infix(_, _)
+                    | Affix::InfixChain(_)
+                    | Affix::PostfixOrInfix(_, _)
+                    | Affix::Ternary(_, _) => {
+                        let position = parser.position(&head);
+                        recover(parser, PrattError::UnexpectedInfix(head, position), tail)?
+                    }
+                };
+                (node, Some(last))
+            }
+        };
+        loop {
+            while let Some(end) = last {
+                let opener = scopes.last().and_then(|i| stack[*i].opener());
+                let info = match next_affix(parser, tail, opener, Denotation::Led, rbp)? {
+                    Some(info) => info,
+                    None => break,
+                };
+                if let (true, Some(precedence)) = (info.starts_operand(), parser.juxtaposition()) {
+                    // Adjacent operands, as if joined by a left-associative operator
+                    let lbp = parser.normalize(precedence);
+                    let next = next_affix(parser, tail, opener, Denotation::Nud, lbp)?;
+                    if !(rbp < lbp && follows(parser, end, lbp)) || next.is_none() {
+                        break;
+                    }
+                    let lhs = node;
+                    stack.push(Frame::Juxtapose {
+                        lhs,
+                        rbp,
+                        last: Affix::Infix(precedence, Associativity::Left),
+                    });
+                    rbp = lbp;
+                    continue 'nud;
+                }
+                let lbp = parser.lbp(info);
+                if rbp < lbp && !follows(parser, end, lbp) && lbp == parser.nbp(end) {
+                    let head = tail.next_input().unwrap();
+                    let position = parser.position(&head);
+                    let error = PrattError::NonAssociative(head, position);
+                    node = recover(parser, error, tail)?;
+                    continue;
+                }
+                if !(rbp < lbp && follows(parser, end, lbp)) {
+                    break;
+                }
+                let head = tail.next_input().unwrap();
+                let info = match info {
+                    Affix::PostfixOrInfix(precedence, associativity) => {
+                        let rbp = parser.rbp(precedence, associativity);
+                        info.resolve(next_affix(parser, tail, opener, Denotation::Nud, rbp)?)
+                    }
+                    info => info,
+                };
+                node = match info {
+                    Affix::Infix(precedence, associativity) => {
+                        let lhs = node;
+                        stack.push(Frame::Infix {
+                            lhs,
+                            op: head,
+                            rbp,
+                            last: info,
+                        });
+                        rbp = parser.rbp(precedence, associativity);
+                        continue 'nud;
+                    }
+                    Affix::InfixChain(precedence) => {
+                        stack.push(Frame::Chain {
+                            head: node,
+                            rest: Vec::new(),
+                            op: head,
+                            precedence,
+                            rbp,
+                            last: info,
+                        });
+                        rbp = parser.rbp(precedence, Associativity::Left);
+                        continue 'nud;
+                    }
+                    Affix::Ternary(precedence, associativity) => {
+                        let els = parser.rbp(precedence, associativity);
+                        scopes.push(stack.len());
+                        stack.push(Frame::Then {
+                            cond: node,
+                            op: head,
+                            rbp,
+                            last: info,
+                            els,
+                        });
+                        rbp = P::MIN;
+                        continue 'nud;
+                    }
+                    Affix::Postfix(_) | Affix::PostfixOrInfix(_, _) => {
+                        parser.postfix(node, head).map_err(PrattError::UserError)?
+                    }
+                    Affix::PostfixWith(_) => match opener {
+                        Some(opener) => {
+                            let mut closes = |input: &T::Input| T::closes(opener, input);
+                            parser.postfix_with(node, head, &mut tail.until(&mut closes))
+                        }
+                        None => parser.postfix_with(node, head, tail),
+                    }?,
+                    Affix::Nilfix | Affix::Circumfix => {
+                        let position = parser.position(&head);
+                        let error = PrattError::UnexpectedNilfix(head, position);
+                        recover(parser, error, tail)?
+                    }
+                    Affix::Prefix(_) | Affix::PrefixPrimary(_) | Affix::Mixfix(_, _) => {
+                        let position = parser.position(&head);
+                        let error = PrattError::UnexpectedPrefix(head, position);
+                        recover(parser, error, tail)?
+                    }
+                };
+                last = Some(info);
+            }
+            // The operand is complete, so pass it to the operator waiting for it
+            let frame = match stack.pop() {
+                Some(frame) => frame,
+                None => return Ok(node),
+            };
+            if scopes.last() == Some(&stack.len()) {
+                scopes.pop();
+            }
+            (node, rbp, last) = match frame {
+                Frame::Prefix { op, rbp, last } => {
+                    let node = parser.prefix(op, node).map_err(PrattError::UserError)?;
+                    (node, rbp, Some(last))
+                }
+                Frame::Infix { lhs, op, rbp, last } => {
+                    let node = parser.infix(lhs, op, node).map_err(PrattError::UserError)?;
+                    (node, rbp, Some(last))
+                }
+                Frame::Juxtapose { lhs, rbp, last } => {
+                    let node = parser.juxtapose(lhs, node)?;
+                    (node, rbp, Some(last))
+                }
+                Frame::Chain {
+                    head,
+                    mut rest,
+                    op,
+                    precedence,
+                    rbp: outer,
+                    last,
+                } => {
+                    rest.push((op, node));
+                    // Continue the run with the next operator of the same precedence
+                    let opener = scopes.last().and_then(|i| stack[*i].opener());
+                    match next_affix(parser, tail, opener, Denotation::Led, rbp)? {
+                        Some(Affix::InfixChain(next)) if next == precedence => {
+                            stack.push(Frame::Chain {
+                                head,
+                                rest,
+                                op: tail.next_input().unwrap(),
+                                precedence,
+                                rbp: outer,
+                                last,
+                            });
+                            rbp = parser.rbp(precedence, Associativity::Left);
+                            continue 'nud;
+                        }
+                        _ => {
+                            let node = parser
+                                .infix_chain(head, rest)
+                                .map_err(PrattError::UserError)?;
+                            (node, outer, Some(last))
+                        }
+                    }
+                }
+                Frame::Mixfix {
+                    mut parts,
+                    keyword,
+                    remaining: 0,
+                    rbp,
+                    last,
+                    ..
+                } => {
+                    parts.push(MixfixPart::Keyword(keyword));
+                    parts.push(MixfixPart::Operand(node));
+                    let node = parser.mixfix(parts)?;
+                    (node, rbp, Some(last))
+                }
+                Frame::Mixfix {
+                    mut parts,
+                    keyword,
+                    remaining,
+                    precedence,
+                    rbp: outer,
+                    last,
+                } => match tail.next_if(|input| T::closes(&keyword, input)) {
+                    Some(separator) => {
+                        parts.push(MixfixPart::Keyword(keyword));
+                        parts.push(MixfixPart::Operand(node));
+                        let remaining = remaining - 1;
+                        if remaining > 0 {
+                            scopes.push(stack.len());
+                        }
+                        stack.push(Frame::Mixfix {
+                            parts,
+                            keyword: separator,
+                            remaining,
+                            precedence,
+                            rbp: outer,
+                            last,
+                        });
+                        rbp = match remaining {
+                            0 => parser.normalize(precedence).lower(),
+                            _ => P::MIN,
+                        };
+                        continue 'nud;
+                    }
+                    None => {
+                        let position = parser.position(&keyword);
+                        let error = PrattError::UnclosedDelimiter(keyword, position);
+                        (recover(parser, error, tail)?, outer, Some(last))
+                    }
+                },
+                Frame::Circumfix { open, rbp, last } => {
+                    let node = match tail.next_if(|input| T::closes(&open, input)) {
+                        Some(close) => parser.circumfix(open, node, close)?,
+                        None => {
+                            let position = parser.position(&open);
+                            let error = PrattError::UnclosedDelimiter(open, position);
+                            recover(parser, error, tail)?
+                        }
+                    };
+                    (node, rbp, Some(last))
+                }
+                Frame::Then {
+                    cond,
+                    op,
+                    rbp: outer,
+                    last,
+                    els,
+                } => match tail.next_if(|input| T::closes(&op, input)) {
+                    Some(delimiter) => {
+                        let then = node;
+                        stack.push(Frame::Else {
+                            cond,
+                            op,
+                            then,
+                            delimiter,
+                            rbp: outer,
+                            last,
+                        });
+                        rbp = els;
+                        continue 'nud;
+                    }
+                    None => {
+                        let position = parser.position(&op);
+                        let error = PrattError::UnclosedDelimiter(op, position);
+                        (recover(parser, error, tail)?, outer, Some(last))
+                    }
+                },
+                Frame::Else {
+                    cond,
+                    op,
+                    then,
+                    delimiter,
+                    rbp,
+                    last,
+                } => {
+                    let node = parser.ternary(cond, op, then, delimiter, node)?;
+                    (node, rbp, Some(last))
+                }
+            };
+        }
+    }
+}
+
+/// Classifies the next input like `lookup`, or returns `None` if there is
+/// none before the input which closes `opener`, like `Tail::until`.
+fn next_affix<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    opener: Option<&T::Input>,
+    denotation: Denotation,
+    rbp: P,
+) -> Result<Option<Affix<P>>, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let next = match opener {
+        Some(opener) => tail.inputs.peek().filter(|input| !T::closes(opener, input)),
+        None => tail.peek(),
+    };
+    match next {
+        Some(_) => lookup(parser, tail, denotation, rbp).map_err(PrattError::UserError),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,67 @@
+//! Named precedence levels, see [`PrecedenceTable`].
+
+use crate::Precedence;
+use alloc::vec::Vec;
+
+/// The distance between adjacent levels, which leaves room for about ten
+/// rounds of [`PrecedenceTable::between`] before two levels touch.
+const GAP: u32 = 1 << 10;
+
+/// Hands out precedences for named levels, from the weakest to the
+/// strongest, so that a `query` can refer to `or` and `and` instead of
+/// numbers which all shift when a level is inserted.
+#[derive(Default)]
+pub struct PrecedenceTable {
+    levels: Vec<(&'static str, Precedence)>,
+}
+
+impl PrecedenceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `name` as a level which binds tighter than all levels
+    /// registered so far, and returns its precedence, or `None` if there is
+    /// no room left above the strongest level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is already registered.
+    pub fn level(&mut self, name: &'static str) -> Option<Precedence> {
+        assert!(
+            self.get(name).is_none(),
+            "level {:?} is already registered",
+            name
+        );
+        let precedence = match self.levels.last() {
+            Some(&(_, last)) => above(last)?,
+            None => Precedence(GAP),
+        };
+        self.levels.push((name, precedence));
+        Some(precedence)
+    }
+
+    /// The precedence of the level `name`, if it is registered.
+    pub fn get(&self, name: &str) -> Option<Precedence> {
+        self.levels
+            .iter()
+            .find(|(level, _)| *level == name)
+            .map(|&(_, precedence)| precedence)
+    }
+
+    /// A precedence which binds tighter than `weaker` but not as tight as
+    /// `stronger`, halfway between the two, or `None` if there is no
+    /// precedence strictly between them, e.g. because they are adjacent.
+    pub fn between(weaker: Precedence, stronger: Precedence) -> Option<Precedence> {
+        match stronger.0.checked_sub(weaker.0) {
+            Some(distance) if distance >= 2 => Some(Precedence(weaker.0 + distance / 2)),
+            _ => None,
+        }
+    }
+}
+
+/// The precedence of a level registered after `last`, unless it would
+/// overflow.
+pub(crate) fn above(last: Precedence) -> Option<Precedence> {
+    last.0.checked_add(GAP).map(Precedence)
+}
@@ -1,76 +1,516 @@
 #![no_std]
+#![allow(clippy::type_complexity)]
 
-#[derive(Copy, Clone)]
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+pub mod builder;
+#[cfg(feature = "alloc")]
+mod collect;
+pub mod iter;
+#[cfg(feature = "alloc")]
+mod iterative;
+#[cfg(feature = "alloc")]
+pub mod levels;
+mod macros;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod unparse;
+mod validate;
+pub mod visit;
+
+#[derive(PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Associativity {
     Left,
     Right,
     Neither,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Copy, Clone)]
+/// A totally ordered binding power.
+///
+/// The parser never compares user precedences directly. It first normalizes
+/// them and then steps one level up or down from the normalized value to
+/// encode associativity, so `raise` and `lower` must stay strictly between
+/// two adjacent normalized precedences.
+pub trait BindingPower: Ord + Copy {
+    /// The weakest binding power, used to start a parse.
+    const MIN: Self;
+    /// The strongest binding power.
+    const MAX: Self;
+    /// Spreads a user precedence out so that `raise`/`lower` have room.
+    fn normalize(self) -> Self;
+    /// The next binding power up from a normalized precedence.
+    fn raise(self) -> Self;
+    /// The next binding power down from a normalized precedence.
+    fn lower(self) -> Self;
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Precedence(pub u32);
 
 impl Precedence {
-    const fn raise(mut self) -> Precedence {
+    pub const fn new(precedence: u32) -> Precedence {
+        Precedence(precedence)
+    }
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+    /// The next stronger precedence, saturating at `Precedence::max()`.
+    pub const fn raise(mut self) -> Precedence {
         self.0 = self.0.saturating_add(1);
         self
     }
-    const fn lower(mut self) -> Precedence {
+    /// The next weaker precedence, saturating at `Precedence::min()`.
+    pub const fn lower(mut self) -> Precedence {
         self.0 = self.0.saturating_sub(1);
         self
     }
-    const fn normalize(mut self) -> Precedence {
+    /// The binding power of operators with this precedence, which leaves
+    /// room for `raise` and `lower` between adjacent precedences.
+    pub const fn normalize(mut self) -> Precedence {
         self.0 = self.0.saturating_mul(10);
         self
     }
-    const fn min() -> Precedence {
+    pub const fn min() -> Precedence {
         Precedence(u32::MIN)
     }
-    const fn max() -> Precedence {
+    pub const fn max() -> Precedence {
         Precedence(u32::MAX)
     }
 }
 
+impl BindingPower for Precedence {
+    const MIN: Precedence = Precedence::min();
+    const MAX: Precedence = Precedence::max();
+    fn normalize(self) -> Precedence {
+        Precedence::normalize(self)
+    }
+    fn raise(self) -> Precedence {
+        Precedence::raise(self)
+    }
+    fn lower(self) -> Precedence {
+        Precedence::lower(self)
+    }
+}
+
+/// A binding power for any totally ordered type, e.g. an enum of precedence
+/// levels with a derived `Ord`, so that a new level can be inserted without
+/// renumbering the others.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub struct Ordinal<T>(Rank<T>);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+enum Rank<T> {
+    Min,
+    Level(T, Step),
+    Max,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+enum Step {
+    Lower,
+    Exact,
+    Raise,
+}
+
+impl<T> Ordinal<T> {
+    pub const fn new(level: T) -> Ordinal<T> {
+        Ordinal(Rank::Level(level, Step::Exact))
+    }
+}
+
+impl<T: Ord + Copy> BindingPower for Ordinal<T> {
+    const MIN: Ordinal<T> = Ordinal(Rank::Min);
+    const MAX: Ordinal<T> = Ordinal(Rank::Max);
+    fn normalize(self) -> Ordinal<T> {
+        match self.0 {
+            Rank::Level(level, _) => Ordinal::new(level),
+            _ => self,
+        }
+    }
+    fn raise(self) -> Ordinal<T> {
+        match self.0 {
+            Rank::Level(level, Step::Lower) => Ordinal(Rank::Level(level, Step::Exact)),
+            Rank::Level(level, _) => Ordinal(Rank::Level(level, Step::Raise)),
+            _ => self,
+        }
+    }
+    fn lower(self) -> Ordinal<T> {
+        match self.0 {
+            Rank::Level(level, Step::Raise) => Ordinal(Rank::Level(level, Step::Exact)),
+            Rank::Level(level, _) => Ordinal(Rank::Level(level, Step::Lower)),
+            _ => self,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
-pub enum Affix {
+pub enum Affix<P = Precedence> {
     Nilfix,
-    Infix(Precedence, Associativity),
-    Prefix(Precedence),
-    Postfix(Precedence),
+    Infix(P, Associativity),
+    /// A left-associative infix operator whose runs are passed to
+    /// [`PrattParser::infix_chain`] at once, e.g. `+` in `a + b - c` if `-`
+    /// is a chained operator of the same precedence. Chained comparisons,
+    /// e.g. `a < b <= c` meaning `a < b and b <= c`, are runs of this kind.
+    #[cfg(feature = "alloc")]
+    InfixChain(P),
+    /// A prefix operator, e.g. `-` in `-a`.
+    ///
+    /// The precedence is the right binding power of the operator, which is
+    /// the only one a prefix operator has: its operand extends over all
+    /// operators that bind tighter. If `-` has a lower precedence than a
+    /// right-associative `^`, then `-2^2` parses as `-(2^2)`, and with a
+    /// higher precedence as `(-2)^2`. The same goes for an operand of `^`,
+    /// so `2^-3^4` parses as `2^(-(3^4))` or `2^((-3)^4)` respectively.
+    /// A keyword whose operand is the entire expression after it, e.g.
+    /// `return` in `return a + b`, is a prefix operator of the lowest
+    /// precedence.
+    Prefix(P),
+    /// A prefix operator which may not be applied to another prefix
+    /// operator, e.g. `typeof` if `typeof typeof a` is an error. It binds
+    /// like `Affix::Prefix`.
+    ///
+    /// This is to prefix operators what `Associativity::Neither` is to infix
+    /// ones: a prefix operator right after it is raised as
+    /// `PrattError::UnexpectedPrefix`, while plain prefix operators such as
+    /// `-` in `- - a` stack.
+    PrefixPrimary(P),
+    Postfix(P),
+    /// A postfix operator which consumes more inputs itself, e.g. `[` in
+    /// `a[b]` or `(` in `f(a, b)`. See [`PrattParser::postfix_with`].
+    ///
+    /// This also covers infix operators whose right-hand side is not an
+    /// expression, e.g. `.` in `a.b` where `b` must be a field name.
+    PostfixWith(P),
+    /// An operator which is infix if an operand follows it and postfix
+    /// otherwise, e.g. `*` in a language where `a * b` multiplies and `a*`
+    /// dereferences. An operand follows if the next input is classified as
+    /// one which starts an expression, e.g. `Affix::Nilfix` or
+    /// `Affix::Prefix`.
+    ///
+    /// It binds like `Affix::Infix` and `Affix::Postfix` respectively, and
+    /// is passed to [`PrattParser::infix`] or [`PrattParser::postfix`].
+    PostfixOrInfix(P, Associativity),
+    /// An infix operator with a middle operand, e.g. `?` in `a ? b : c`.
+    ///
+    /// The middle operand is parsed at the lowest precedence until the
+    /// delimiter that [`PrattParser::closes`] the operator. The last operand
+    /// is parsed like the right operand of an infix operator.
+    Ternary(P, Associativity),
+    /// An opening delimiter, e.g. `(` in `(a)`.
+    ///
+    /// The enclosed operand is parsed at the lowest precedence until the
+    /// delimiter that [`PrattParser::closes`] the opening one. The result is
+    /// an operand, like a primary expression.
+    Circumfix,
+    /// A prefix operator made up of keywords, e.g. `if` in
+    /// `if a then b else c`, followed by the given number of separators.
+    ///
+    /// Each separator is the input which [`PrattParser::closes`] the previous
+    /// keyword, e.g. `then` closes `if` and `else` closes `then`. The operands
+    /// between keywords are parsed at the lowest precedence, and the last
+    /// operand like the operand of a prefix operator. See
+    /// [`PrattParser::mixfix`].
+    #[cfg(feature = "alloc")]
+    Mixfix(P, usize),
+}
+
+//         <lbp>  <rbp>  <nbp> <kind>
+// Nilfix:  MIN |    - |  MAX | nud
+// Prefix:  MIN | bp-1 |  MAX | nud
+// Postfix:  bp |    - |  MAX | led
+// InfixL:   bp |   bp | bp+1 | led
+// InfixR:   bp | bp-1 | bp+1 | led
+// InfixN:   bp | bp+1 |   bp | led
+//
+// Ternary operators bind like infix operators of the same associativity,
+// chained infix operators like left-associative ones, postfix operators
+// with arguments like plain postfix operators, circumfix operators like
+// nilfix ones, and mixfix operators like prefix ones. Operators which are
+// postfix or infix bind like infix ones until the parser has decided which
+// they are. Delimited operands are parsed at MIN.
+impl<P: BindingPower> Affix<P> {
+    /// The left binding power of the operator, i.e. how tightly it binds to
+    /// the operand on its left. Operators without one have `P::MIN`.
+    pub fn lbp(self) -> P {
+        self.lbp_with(P::normalize)
+    }
+
+    /// The next binding power of the operator, i.e. how tightly a following
+    /// operator may bind to the expression ending in this one. A following
+    /// operator extends the expression only if its left binding power is
+    /// below this.
+    pub fn nbp(self) -> P {
+        self.nbp_with(P::normalize)
+    }
+
+    /// The binding power at which the operand on the right of the operator
+    /// is parsed, or `None` if it has no such operand.
+    pub fn rbp(self) -> Option<P> {
+        self.rbp_with(P::normalize)
+    }
+
+    pub(crate) fn lbp_with(self, normalize: impl FnOnce(P) -> P) -> P {
+        match self {
+            Affix::Nilfix => P::MIN,
+            Affix::Circumfix => P::MIN,
+            Affix::Prefix(_) => P::MIN,
+            Affix::PrefixPrimary(_) => P::MIN,
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(_, _) => P::MIN,
+            Affix::Postfix(precedence) => normalize(precedence),
+            Affix::PostfixWith(precedence) => normalize(precedence),
+            Affix::PostfixOrInfix(precedence, _) => normalize(precedence),
+            Affix::Infix(precedence, _) => normalize(precedence),
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(precedence) => normalize(precedence),
+            Affix::Ternary(precedence, _) => normalize(precedence),
+        }
+    }
+
+    pub(crate) fn nbp_with(self, normalize: impl FnOnce(P) -> P) -> P {
+        match self {
+            Affix::Nilfix => P::MAX,
+            Affix::Circumfix => P::MAX,
+            Affix::Prefix(_) => P::MAX,
+            Affix::PrefixPrimary(_) => P::MAX,
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(_, _) => P::MAX,
+            Affix::Postfix(_) => P::MAX,
+            Affix::PostfixWith(_) => P::MAX,
+            Affix::PostfixOrInfix(precedence, associativity) => {
+                Affix::Infix(precedence, associativity).nbp_with(normalize)
+            }
+            Affix::Infix(precedence, Associativity::Left) => normalize(precedence).raise(),
+            Affix::Infix(precedence, Associativity::Right) => normalize(precedence).raise(),
+            Affix::Infix(precedence, Associativity::Neither) => normalize(precedence),
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(precedence) => normalize(precedence).raise(),
+            Affix::Ternary(precedence, Associativity::Left) => normalize(precedence).raise(),
+            Affix::Ternary(precedence, Associativity::Right) => normalize(precedence).raise(),
+            Affix::Ternary(precedence, Associativity::Neither) => normalize(precedence),
+        }
+    }
+
+    pub(crate) fn rbp_with(self, normalize: impl FnOnce(P) -> P) -> Option<P> {
+        let (precedence, associativity) = match self {
+            Affix::Nilfix | Affix::Circumfix => return None,
+            Affix::Postfix(_) | Affix::PostfixWith(_) => return None,
+            Affix::Prefix(precedence) | Affix::PrefixPrimary(precedence) => {
+                return Some(normalize(precedence).lower())
+            }
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(precedence, _) => return Some(normalize(precedence).lower()),
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(precedence) => (precedence, Associativity::Left),
+            Affix::Infix(precedence, associativity) => (precedence, associativity),
+            Affix::PostfixOrInfix(precedence, associativity) => (precedence, associativity),
+            Affix::Ternary(precedence, associativity) => (precedence, associativity),
+        };
+        let precedence = normalize(precedence);
+        Some(match associativity {
+            Associativity::Left => precedence,
+            Associativity::Right => precedence.lower(),
+            Associativity::Neither => precedence.raise(),
+        })
+    }
+
+    /// The precedence and associativity of an operator with operands on both
+    /// sides, or `None` for any other affix.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn binary(self) -> Option<(P, Associativity)> {
+        match self {
+            Affix::Infix(precedence, associativity)
+            | Affix::PostfixOrInfix(precedence, associativity)
+            | Affix::Ternary(precedence, associativity) => Some((precedence, associativity)),
+            Affix::InfixChain(precedence) => Some((precedence, Associativity::Left)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is a non-associative operator.
+    pub(crate) fn non_associative(self) -> bool {
+        matches!(
+            self,
+            Affix::Infix(_, Associativity::Neither)
+                | Affix::PostfixOrInfix(_, Associativity::Neither)
+                | Affix::Ternary(_, Associativity::Neither)
+        )
+    }
+
+    /// Returns true if an input of this affix begins an operand.
+    pub(crate) fn starts_operand(self) -> bool {
+        match self {
+            Affix::Nilfix | Affix::Circumfix => true,
+            Affix::Prefix(_) | Affix::PrefixPrimary(_) => true,
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(_, _) => true,
+            _ => false,
+        }
+    }
+
+    /// Decides whether an `Affix::PostfixOrInfix` operator is infix or
+    /// postfix, given the nud classification of the input after it.
+    pub(crate) fn resolve(self, next: Option<Affix<P>>) -> Affix<P> {
+        let Affix::PostfixOrInfix(precedence, associativity) = self else {
+            return self;
+        };
+        match next {
+            Some(next) if next.starts_operand() => Affix::Infix(precedence, associativity),
+            _ => Affix::Postfix(precedence),
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum PrattError<I: core::fmt::Debug, E: core::fmt::Display> {
+/// A keyword or an operand of an `Affix::Mixfix` operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MixfixPart<I, O> {
+    Keyword(I),
+    Operand(O),
+}
+
+/// An error produced by the parser.
+///
+/// Errors that point at an input also carry its position, as reported by
+/// [`PrattParser::position`].
+#[derive(Debug, Clone)]
+pub enum PrattError<I: core::fmt::Debug, E: core::fmt::Display, P = ()> {
     UserError(E),
     EmptyInput,
-    UnexpectedNilfix(I),
-    UnexpectedPrefix(I),
-    UnexpectedInfix(I),
-    UnexpectedPostfix(I),
+    UnexpectedNilfix(I, P),
+    UnexpectedPrefix(I, P),
+    UnexpectedInfix(I, P),
+    UnexpectedPostfix(I, P),
+    UnclosedDelimiter(I, P),
+    /// An operator whose operand is missing, e.g. `!` in `1 + !`. An
+    /// expression which is empty as a whole is `EmptyInput` instead.
+    MissingOperand(I, P),
+    /// An operator following a non-associative operator of the same
+    /// precedence, e.g. the second `=` in `a = b = c`, or `<` in `a = b < c`
+    /// if `<` is non-associative and as tight as `=`.
+    NonAssociative(I, P),
+    /// An expression which is not followed by its terminator, see
+    /// [`PrattParser::parse_terminated`], with the input found in its place,
+    /// or `None` at the end of the inputs.
+    MissingTerminator(Option<(I, P)>),
+    /// An operator whose constructor is not implemented, e.g. an input
+    /// classified as `Affix::Ternary` by a parser which does not override
+    /// [`PrattParser::ternary`].
+    UnsupportedAffix(I, P),
+    /// Adjacent operands, e.g. `f x`, in a parser which sets a
+    /// [`PrattParser::juxtaposition`] precedence without overriding
+    /// [`PrattParser::juxtapose`].
+    UnsupportedJuxtaposition,
+    DepthExceeded,
+}
+
+impl<I: core::fmt::Debug, E: core::fmt::Display, P> PrattError<I, E, P> {
+    /// Returns the position of the offending input, if there is one.
+    pub fn position(&self) -> Option<&P> {
+        match self {
+            PrattError::UserError(_)
+            | PrattError::EmptyInput
+            | PrattError::UnsupportedJuxtaposition
+            | PrattError::DepthExceeded => None,
+            PrattError::UnexpectedNilfix(_, p)
+            | PrattError::UnexpectedPrefix(_, p)
+            | PrattError::UnexpectedInfix(_, p)
+            | PrattError::UnexpectedPostfix(_, p)
+            | PrattError::UnclosedDelimiter(_, p)
+            | PrattError::MissingOperand(_, p)
+            | PrattError::NonAssociative(_, p)
+            | PrattError::UnsupportedAffix(_, p) => Some(p),
+            PrattError::MissingTerminator(found) => found.as_ref().map(|(_, p)| p),
+        }
+    }
 }
 
-impl<I: core::fmt::Debug, E: core::fmt::Display> core::fmt::Display for PrattError<I, E> {
+impl<I: core::fmt::Debug, E: core::fmt::Display, P> core::fmt::Display for PrattError<I, E, P> {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             PrattError::UserError(e) => write!(f, "{}", e),
             PrattError::EmptyInput => write!(f, "Pratt parser was called with empty input."),
-            PrattError::UnexpectedNilfix(t) => {
+            PrattError::UnexpectedNilfix(t, _) => {
                 write!(f, "Expected Infix or Postfix, found Nilfix {:?}", t)
             }
-            PrattError::UnexpectedPrefix(t) => {
+            PrattError::UnexpectedPrefix(t, _) => {
                 write!(f, "Expected Infix or Postfix, found Prefix {:?}", t)
             }
-            PrattError::UnexpectedInfix(t) => {
+            PrattError::UnexpectedInfix(t, _) => {
                 write!(f, "Expected Nilfix or Prefix, found Infix {:?}", t)
             }
-            PrattError::UnexpectedPostfix(t) => {
+            PrattError::UnexpectedPostfix(t, _) => {
                 write!(f, "Expected Nilfix or Prefix, found Postfix {:?}", t)
             }
+            PrattError::UnclosedDelimiter(t, _) => {
+                write!(f, "Expected a delimiter closing {:?}", t)
+            }
+            PrattError::MissingOperand(t, _) => {
+                write!(f, "Expected an operand after {:?}", t)
+            }
+            PrattError::NonAssociative(t, _) => {
+                write!(
+                    f,
+                    "Expected parentheses around non-associative operator {:?}",
+                    t
+                )
+            }
+            PrattError::MissingTerminator(Some((t, _))) => {
+                write!(f, "Expected a terminator, found {:?}", t)
+            }
+            PrattError::MissingTerminator(None) => {
+                write!(f, "Expected a terminator, found the end of input")
+            }
+            PrattError::UnsupportedAffix(t, _) => {
+                write!(f, "Operator {:?} is not supported by this parser", t)
+            }
+            PrattError::UnsupportedJuxtaposition => {
+                write!(f, "Adjacent operands are not supported by this parser")
+            }
+            PrattError::DepthExceeded => write!(f, "Pratt parser exceeded the maximum depth."),
+        }
+    }
+}
+
+/// Lets `?` return the user's error from a constructor whose error type is
+/// a `PrattError`, e.g. [`PrattParser::postfix_with`].
+impl<I: core::fmt::Debug, E: core::fmt::Display, P> From<E> for PrattError<I, E, P> {
+    fn from(error: E) -> Self {
+        PrattError::UserError(error)
+    }
+}
+
+/// Behind the `std` feature. The source of a `PrattError::UserError` is the
+/// user's error.
+#[cfg(feature = "std")]
+impl<I, E, P> std::error::Error for PrattError<I, E, P>
+where
+    I: core::fmt::Debug,
+    E: std::error::Error + 'static,
+    P: core::fmt::Debug,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PrattError::UserError(e) => Some(e),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NoError;
 
 impl core::fmt::Display for NoError {
@@ -79,20 +519,287 @@ impl core::fmt::Display for NoError {
     }
 }
 
-pub type Result<T> = core::result::Result<T, NoError>;
+#[cfg(feature = "std")]
+impl std::error::Error for NoError {}
+
+/// A [`core::result::Result`] whose error type defaults to [`NoError`].
+///
+/// The error type can still be spelled out, so glob-importing this alias
+/// keeps `Result<T, E>` meaning the same as in `core`.
+pub type Result<T, E = NoError> = core::result::Result<T, E>;
+
+/// The inputs that remain to be parsed.
+///
+/// A `Tail` wraps the `Peekable` iterator of a parse. It behaves like the
+/// iterator itself, except that it ends early at a sentinel input when one
+/// has been set with [`Tail::until`]. The sentinel is never consumed.
+///
+/// Like the iterator, a `Tail` looks a single input ahead. An input whose
+/// role depends on inputs further ahead, e.g. `<` opening the type arguments
+/// in `f<T>` but comparing in `a < b`, can be told apart by
+/// [`PrattParser::query_with_context`], which may look further ahead.
+///
+/// A `Tail` also remembers how its next input was classified, by `classify`
+/// and by `query_led`, until the input is consumed. An input is thus queried
+/// once in each position even when several nested expressions end in front
+/// of it, or when the parser looks ahead at it before parsing it, so a
+/// `query` which answers differently each time cannot change its mind
+/// halfway. A `Tail` should therefore only be parsed by parsers which
+/// classify inputs alike.
+pub struct Tail<'a, Inputs: Iterator, P = Precedence> {
+    inputs: &'a mut core::iter::Peekable<Inputs>,
+    stop: Option<&'a mut dyn FnMut(&Inputs::Item) -> bool>,
+    depth: usize,
+    memo: Memo<'a, P>,
+}
+
+/// What a `Tail` knows about its inputs: how many it consumed, and how its
+/// next input was classified. A tail made by `Tail::until` shares this with
+/// the tail it was made from, so that the classification outlives it.
+struct Seen<P> {
+    consumed: usize,
+    nud: Option<Option<Affix<P>>>,
+    led: Option<Option<Affix<P>>>,
+}
+
+enum Memo<'a, P> {
+    Own(Seen<P>),
+    Outer(&'a mut Seen<P>),
+}
+
+impl<P> Memo<'_, P> {
+    fn get(&mut self) -> &mut Seen<P> {
+        match self {
+            Memo::Own(seen) => seen,
+            Memo::Outer(seen) => seen,
+        }
+    }
+}
+
+impl<'a, Inputs: Iterator, P> Tail<'a, Inputs, P> {
+    pub fn new(inputs: &'a mut core::iter::Peekable<Inputs>) -> Self {
+        Tail {
+            inputs,
+            stop: None,
+            depth: 0,
+            memo: Memo::Own(Seen {
+                consumed: 0,
+                nud: None,
+                led: None,
+            }),
+        }
+    }
+
+    /// Returns a tail which ends at the first input for which `stop` returns true.
+    pub fn until<'b>(
+        &'b mut self,
+        stop: &'b mut dyn FnMut(&Inputs::Item) -> bool,
+    ) -> Tail<'b, Inputs, P> {
+        Tail {
+            inputs: self.inputs,
+            stop: Some(stop),
+            depth: self.depth,
+            memo: Memo::Outer(self.memo.get()),
+        }
+    }
+
+    /// The number of inputs consumed so far, including those consumed from
+    /// tails made from this one by `until`.
+    pub fn consumed(&self) -> usize {
+        match &self.memo {
+            Memo::Own(seen) => seen.consumed,
+            Memo::Outer(seen) => seen.consumed,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&Inputs::Item> {
+        let input = self.inputs.peek()?;
+        if let Some(stop) = &mut self.stop {
+            if stop(input) {
+                return None;
+            }
+        }
+        Some(input)
+    }
+
+    /// Consumes the next input if `func` returns true for it, even if it is
+    /// the sentinel. This is how a closing delimiter that also ends the
+    /// enclosing expression, e.g. the inner `)` in `((a))`, is consumed.
+    pub fn next_if(&mut self, func: impl FnOnce(&Inputs::Item) -> bool) -> Option<Inputs::Item> {
+        let input = self.inputs.next_if(func)?;
+        let seen = self.memo.get();
+        seen.nud = None;
+        seen.led = None;
+        seen.consumed += 1;
+        Some(input)
+    }
+
+    /// Consumes the next input, even if it is the sentinel.
+    pub(crate) fn next_input(&mut self) -> Option<Inputs::Item> {
+        self.next_if(|_| true)
+    }
+}
+
+impl<Inputs: Iterator, P> Iterator for Tail<'_, Inputs, P> {
+    type Item = Inputs::Item;
+
+    fn next(&mut self) -> Option<Inputs::Item> {
+        self.peek()?;
+        self.next_input()
+    }
+}
+
+/// Where an input is classified, see [`QueryContext::denotation`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Denotation {
+    /// In operand position, where `PrattParser::classify` is asked.
+    Nud,
+    /// After a complete operand, where `PrattParser::query_led` is asked.
+    Led,
+}
+
+/// The input which [`PrattParser::query_with_context`] classifies, with
+/// where it is classified and a bounded view of the inputs after it.
+pub struct QueryContext<'a, Inputs: Iterator, P = Precedence> {
+    inputs: &'a mut core::iter::Peekable<Inputs>,
+    denotation: Denotation,
+    rbp: P,
+}
+
+impl<Inputs: Iterator, P: Copy> QueryContext<'_, Inputs, P> {
+    /// The input to classify.
+    pub fn input(&mut self) -> &Inputs::Item {
+        self.inputs.peek().unwrap()
+    }
+
+    /// Whether the input is in operand position or follows an operand.
+    pub fn denotation(&self) -> Denotation {
+        self.denotation
+    }
+
+    /// The right binding power of the expression which the input is first
+    /// classified in. An input is classified once in each position, so when
+    /// that expression ends in front of it, the enclosing expressions, whose
+    /// right binding powers are weaker, stick to the same answer.
+    pub fn rbp(&self) -> P {
+        self.rbp
+    }
+}
+
+impl<Inputs, P> QueryContext<'_, Inputs, P>
+where
+    Inputs: Iterator + Clone,
+    Inputs::Item: Clone,
+{
+    /// Returns the `n`th input after the one to classify, counting from
+    /// zero, without consuming anything. Each call clones the inputs and
+    /// steps the clone `n + 1` times, so `n` should stay small. The view
+    /// does not end at the sentinel of a tail made by `Tail::until`.
+    pub fn lookahead(&self, n: usize) -> Option<Inputs::Item> {
+        let mut inputs = core::iter::Peekable::clone(self.inputs);
+        inputs.nth(n + 1)
+    }
+}
 
-pub trait PrattParser<Inputs>
+pub trait PrattParser<Inputs, P = Precedence>
 where
+    P: BindingPower,
     Inputs: Iterator<Item = Self::Input>,
 {
     type Error: core::fmt::Display;
     type Input: core::fmt::Debug;
+    /// The expression built by the parser. It may borrow from the inputs,
+    /// e.g. be `Expr<'a>` for inputs of type `&'a str`, by implementing the
+    /// trait for every lifetime `'a` with `impl<'a, I> PrattParser<I> for
+    /// Parser where I: Iterator<Item = &'a str>`.
     type Output: Sized;
+    /// Location of an input in the source, e.g. a byte offset or a span.
+    type Position: Default;
+
+    /// Classifies an input. The answer may depend on the parser's state,
+    /// e.g. an associativity chosen at runtime, and need not be the same for
+    /// equal inputs: each input is queried at most once in operand position
+    /// and once after an operand, and the parser sticks to that answer.
+    ///
+    /// To look at an input only once, e.g. to find both the `Affix` and the
+    /// operation of `+`, the inputs can be mapped to a tagged input before
+    /// parsing, which `query` and the callbacks then take apart.
+    ///
+    /// Whether an input follows an operand is told by `query_led`, and the
+    /// inputs after it by `query_with_context`. Other context, e.g. being
+    /// inside a command substitution where `|` binds differently, can be
+    /// kept in the parser by the callback which parses the nested
+    /// expression, since inputs are classified only once they are reached.
+    fn query(&mut self, input: &Self::Input) -> core::result::Result<Affix<P>, Self::Error>;
+
+    /// Classifies `input` like `query`, or returns `None` if `input` ends the
+    /// expression, e.g. `then` in `if a then b`. Parsing then stops in front
+    /// of `input` and leaves it in the inputs, as if they ended there.
+    /// Defaults to `query`, which classifies every input.
+    fn classify(
+        &mut self,
+        input: &Self::Input,
+    ) -> core::result::Result<Option<Affix<P>>, Self::Error> {
+        self.query(input).map(Some)
+    }
+
+    /// Classifies `input` like `classify`, but where it follows a complete
+    /// operand, i.e. where it is expected to be an infix or postfix operator.
+    /// This lets the same input have different roles, e.g. `-` as a prefix
+    /// operator in `-a` but an infix operator in `a - b`, without a separate
+    /// pass over the inputs. Defaults to `classify`.
+    ///
+    /// Here `input` is classified on its own. An operator made up of several
+    /// inputs, e.g. `not in`, can be classified by its first input as
+    /// `Affix::PostfixWith`, whose callback consumes the rest of the operator
+    /// and its right operand. When the first input is also an operator on
+    /// its own, e.g. `>` and `>>=`, `query_with_context` can look at the
+    /// inputs after it to tell them apart.
+    fn query_led(
+        &mut self,
+        input: &Self::Input,
+    ) -> core::result::Result<Option<Affix<P>>, Self::Error> {
+        self.classify(input)
+    }
 
-    fn query(&mut self, input: &Self::Input) -> core::result::Result<Affix, Self::Error>;
+    /// Classifies the input of `context` like `classify` in operand position
+    /// and like `query_led` after an operand, which it defaults to. This is
+    /// the hook the parser asks, and `context` also tells the binding power
+    /// it parses at and lets it look a few inputs further ahead, e.g. to
+    /// classify `not` as the start of `not in` only where `in` follows.
+    fn query_with_context(
+        &mut self,
+        context: &mut QueryContext<'_, Inputs, P>,
+    ) -> core::result::Result<Option<Affix<P>>, Self::Error> {
+        match context.denotation() {
+            Denotation::Nud => self.classify(context.input()),
+            Denotation::Led => self.query_led(context.input()),
+        }
+    }
 
-    fn primary(&mut self, input: Self::Input) -> core::result::Result<Self::Output, Self::Error>;
+    /// Construct a primary expression. `tail` holds the inputs after
+    /// `input`, so a primary may consume more of them, e.g. to parse a
+    /// parenthesized sub-expression from a flat token stream.
+    fn primary(
+        &mut self,
+        input: Self::Input,
+        tail: &mut Tail<'_, Inputs, P>,
+    ) -> core::result::Result<Self::Output, Self::Error>;
 
+    /// Construct an infix expression. Only called for inputs classified as
+    /// infix, and a parser whose `query` may classify an input which this
+    /// does not handle should return an error for it rather than panic.
+    ///
+    /// Constructors are called once all operands of their operator have
+    /// been constructed, and operands in the order they appear, i.e. in
+    /// post-order. A parser whose `Output` is `()` can therefore push each
+    /// primary and operator onto a list, which then holds the expression in
+    /// reverse Polish notation, with operators in the order of evaluation.
+    ///
+    /// The operands are moved into this and every other constructor, so an
+    /// error which should not lose them, e.g. for an editor which shows the
+    /// partial tree, has to carry them itself: `Self::Error` may hold an
+    /// output, and is returned as `PrattError::UserError`.
     fn infix(
         &mut self,
         lhs: Self::Output,
@@ -106,121 +813,4660 @@ where
         rhs: Self::Output,
     ) -> core::result::Result<Self::Output, Self::Error>;
 
+    /// Construct an expression from a run of `Affix::InfixChain` operators
+    /// of the same precedence, e.g. `a + b + c`, given its first operand
+    /// and the operators with the operands that follow them. Defaults to
+    /// folding the run from the left with `infix`.
+    #[cfg(feature = "alloc")]
+    fn infix_chain(
+        &mut self,
+        head: Self::Output,
+        rest: alloc::vec::Vec<(Self::Input, Self::Output)>,
+    ) -> core::result::Result<Self::Output, Self::Error> {
+        rest.into_iter()
+            .try_fold(head, |lhs, (op, rhs)| self.infix(lhs, op, rhs))
+    }
+
     fn postfix(
         &mut self,
         lhs: Self::Output,
         op: Self::Input,
     ) -> core::result::Result<Self::Output, Self::Error>;
 
+    /// Construct a postfix expression whose operator is followed by more
+    /// inputs, e.g. `a[b]`. Only called for inputs classified as
+    /// `Affix::PostfixWith`. `tail` holds the inputs after `op`, and this
+    /// method is expected to consume them up to and including the closing
+    /// input, e.g. `]`. An operator like `.` in `a.b` can instead take
+    /// exactly one input with `tail.next()`, and one whose inputs are
+    /// optional, e.g. `? : b` in `a ? : b` next to a plain `a ?`, can look at
+    /// them with `tail.peek()` or consume them only if present with
+    /// `tail.next_if`.
+    ///
+    /// Unlike the required constructors, this and the other optional ones
+    /// return a `PrattError`, so the errors of a nested parse of `tail` pass
+    /// through with `?`, as do the parser's own errors, which become a
+    /// `UserError`. Defaults to `PrattError::UnsupportedAffix` for `op`.
+    fn postfix_with(
+        &mut self,
+        _lhs: Self::Output,
+        op: Self::Input,
+        _tail: &mut Tail<'_, Inputs, P>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        let position = self.position(&op);
+        Err(PrattError::UnsupportedAffix(op, position))
+    }
+
+    /// Construct a ternary expression, e.g. `a ? b : c`. Only called for
+    /// inputs classified as `Affix::Ternary`. Defaults to
+    /// `PrattError::UnsupportedAffix` for `op`.
+    fn ternary(
+        &mut self,
+        _cond: Self::Output,
+        op: Self::Input,
+        _then: Self::Output,
+        _delimiter: Self::Input,
+        _els: Self::Output,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        let position = self.position(&op);
+        Err(PrattError::UnsupportedAffix(op, position))
+    }
+
+    /// Construct a delimited expression, e.g. `(a)`. Only called for inputs
+    /// classified as `Affix::Circumfix`. Defaults to
+    /// `PrattError::UnsupportedAffix` for `open`.
+    fn circumfix(
+        &mut self,
+        open: Self::Input,
+        _inner: Self::Output,
+        _close: Self::Input,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        let position = self.position(&open);
+        Err(PrattError::UnsupportedAffix(open, position))
+    }
+
+    /// Construct a mixfix expression, e.g. `if a then b else c`, from its
+    /// keywords and operands in order. Only called for inputs classified as
+    /// `Affix::Mixfix`. Defaults to `PrattError::UnsupportedAffix` for the
+    /// first keyword.
+    #[cfg(feature = "alloc")]
+    fn mixfix(
+        &mut self,
+        parts: alloc::vec::Vec<MixfixPart<Self::Input, Self::Output>>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        match parts.into_iter().next() {
+            Some(MixfixPart::Keyword(keyword)) => {
+                let position = self.position(&keyword);
+                Err(PrattError::UnsupportedAffix(keyword, position))
+            }
+            _ => unreachable!("a mixfix expression begins with a keyword"),
+        }
+    }
+
+    /// The precedence at which two adjacent operands are combined, e.g. a
+    /// function and its argument in `f x`, as if a left-associative infix
+    /// operator stood between them. An input after an operand is taken to
+    /// begin the second operand if `query_led` classifies it as `Nilfix`,
+    /// `Circumfix`, `Prefix`, `PrefixPrimary` or `Mixfix`. Defaults to
+    /// `None`, where the expression instead ends in front of such an input.
+    fn juxtaposition(&self) -> Option<P> {
+        None
+    }
+
+    /// Construct an expression from two adjacent operands, e.g. the
+    /// application `f x`. Only called if `juxtaposition` returns a
+    /// precedence. Defaults to `PrattError::UnsupportedJuxtaposition`.
+    fn juxtapose(
+        &mut self,
+        _lhs: Self::Output,
+        _rhs: Self::Output,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        Err(PrattError::UnsupportedJuxtaposition)
+    }
+
+    /// Returns true if `delimiter` closes the operator `op`, e.g. `:` closes
+    /// `?` in `a ? b : c` and `)` closes `(` in `(a)`. Defaults to false.
+    fn closes(_op: &Self::Input, _delimiter: &Self::Input) -> bool {
+        false
+    }
+
+    /// Recover from a syntax error, e.g. by returning a placeholder node for
+    /// the missing or malformed operand. Called where any error other than
+    /// a `UserError` or `DepthExceeded` is raised, after the offending input
+    /// has been consumed. Errors returned by constructors, e.g. by
+    /// `postfix_with`, are not passed to this.
+    /// `tail` may be advanced, e.g. to skip to a synchronization point.
+    ///
+    /// When this returns `Some`, parsing continues with the returned node in
+    /// place of the error. Defaults to `None`, which aborts the parse.
+    fn recover(
+        &mut self,
+        _error: &PrattError<Self::Input, Self::Error, Self::Position>,
+        _tail: &mut Tail<'_, Inputs, P>,
+    ) -> Option<Self::Output> {
+        None
+    }
+
+    /// Called instead of raising `EmptyInput` when an expression is empty as
+    /// a whole, e.g. for `parse` of no inputs or `parse_until` in front of
+    /// its sentinel. Returns a node which stands in for the expression, or
+    /// an error to fail with as a `UserError`. Defaults to `Ok(None)`, which
+    /// raises `EmptyInput`, and thereby calls `recover`.
+    fn on_empty(&mut self) -> core::result::Result<Option<Self::Output>, Self::Error> {
+        Ok(None)
+    }
+
+    /// The maximum number of operands which may be nested inside each other,
+    /// e.g. 2 for `!!a` or `((a))`. Exceeding it fails the parse with
+    /// `DepthExceeded` instead of overflowing the stack. Defaults to `None`,
+    /// which means no limit.
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the position of `input`, which is attached to errors that
+    /// point at it. Defaults to `Self::Position::default()`.
+    fn position(&self, _input: &Self::Input) -> Self::Position {
+        Self::Position::default()
+    }
+
+    /// Parses one expression from `inputs`, which is an iterator or anything
+    /// that turns into one, e.g. a `Vec` or an array of inputs.
+    ///
+    /// Parsing stops at the first input which cannot extend the expression,
+    /// e.g. the `2` in `1 2`. That input has already been taken from
+    /// `inputs` to look at it, so when passing `&mut iter` it is missing
+    /// from `iter` afterwards. Use `parse_peekable` to keep it.
     fn parse(
         &mut self,
-        inputs: Inputs,
-    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error>> {
-        self.parse_input(&mut inputs.peekable(), Precedence::min())
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.parse_input(&mut Tail::new(&mut inputs.into_iter().peekable()), P::MIN)
+    }
+
+    /// Clears any state which the parser accumulates while parsing, e.g. an
+    /// interner, so that the next parse starts afresh. Called by
+    /// `parse_fresh`, and does nothing by default.
+    fn reset(&mut self) {}
+
+    /// Parses `inputs` like `parse`, after calling `reset`, for reusing a
+    /// parser on an input which is unrelated to those it parsed before.
+    fn parse_fresh(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.reset();
+        self.parse(inputs)
+    }
+
+    /// Parses `inputs` like `parse`, for parsers whose callbacks compute a
+    /// value directly instead of building a tree, e.g. a calculator.
+    ///
+    /// The parser itself never allocates. Pending operators and operands are
+    /// kept on the call stack and every operator is applied as soon as its
+    /// operands are known, so an `Output` such as `i64` is evaluated without
+    /// any memory beyond what `inputs` and the callbacks use. The only
+    /// exceptions are the operands collected for `Affix::InfixChain` and
+    /// `Affix::Mixfix`, which need the `alloc` feature anyway.
+    fn evaluate(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.parse(inputs)
     }
 
+    /// Parses one expression from the front of `inputs`.
+    ///
+    /// Parsing stops in front of the first input which cannot extend the
+    /// expression, e.g. the `3` in `1+2 3`. That input and all inputs after
+    /// it are left in `inputs`, so a following `inputs.peek()` returns it.
+    /// If the parse fails, the state of `inputs` is unspecified.
     fn parse_peekable(
         &mut self,
         inputs: &mut core::iter::Peekable<Inputs>,
-    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error>> {
-        self.parse_input(inputs, Precedence::min())
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.parse_with_min_precedence(inputs, P::MIN)
+    }
+
+    /// Parses like `parse_peekable`, and also returns the number of inputs
+    /// which the expression consumed, e.g. 3 for `1+2` in `1+2 3 4`.
+    fn parse_counted(
+        &mut self,
+        inputs: &mut core::iter::Peekable<Inputs>,
+    ) -> core::result::Result<
+        (Self::Output, usize),
+        PrattError<Self::Input, Self::Error, Self::Position>,
+    > {
+        let mut tail = Tail::new(inputs);
+        let output = self.parse_input(&mut tail, P::MIN)?;
+        Ok((output, tail.consumed()))
     }
 
+    /// Parses an expression whose operators all bind tighter than `min`,
+    /// leaving the first operator that does not in `inputs`.
+    ///
+    /// `min` is compared against normalized precedences, so pass e.g.
+    /// `self.normalize(Precedence(3))` to stop at operators of precedence 3.
+    /// `P::MIN` is equivalent to `parse_peekable`, and `P::MAX` parses a
+    /// single null-denotation, i.e. a primary or a prefix operator applied
+    /// to its operand.
+    ///
+    /// This method is part of the stable interface, unlike `parse_input`
+    /// which it wraps.
+    fn parse_with_min_precedence(
+        &mut self,
+        inputs: &mut core::iter::Peekable<Inputs>,
+        min: P,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.parse_input(&mut Tail::new(inputs), min)
+    }
+
+    /// Parses like `parse_peekable`, but stops in front of the first input
+    /// for which `stop` returns true, also inside nested sub-expressions.
+    /// The sentinel is left in `inputs`. If it comes first, the result is
+    /// `EmptyInput`.
+    fn parse_until<F: FnMut(&Self::Input) -> bool>(
+        &mut self,
+        inputs: &mut core::iter::Peekable<Inputs>,
+        mut stop: F,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        self.parse_input(&mut Tail::new(inputs).until(&mut stop), P::MIN)
+    }
+
+    /// Parses like `parse_until`, and then consumes the sentinel, which must
+    /// follow the expression, e.g. `do` after the condition of `while a do`.
+    /// Returns the expression together with the sentinel. Any other input
+    /// in its place is consumed and returned in `MissingTerminator`.
+    fn parse_terminated<F: FnMut(&Self::Input) -> bool>(
+        &mut self,
+        inputs: &mut core::iter::Peekable<Inputs>,
+        mut stop: F,
+    ) -> core::result::Result<
+        (Self::Output, Self::Input),
+        PrattError<Self::Input, Self::Error, Self::Position>,
+    > {
+        let output = self.parse_until(inputs, &mut stop)?;
+        match inputs.next() {
+            Some(input) if stop(&input) => Ok((output, input)),
+            Some(input) => {
+                let position = self.position(&input);
+                Err(PrattError::MissingTerminator(Some((input, position))))
+            }
+            None => Err(PrattError::MissingTerminator(None)),
+        }
+    }
+
+    /// Returns an iterator which parses one expression of `inputs` per call
+    /// to `next`, e.g. for a REPL. Each expression is ended by an input for
+    /// which `terminator` returns true, and the terminator is consumed. A
+    /// final terminator is optional.
+    fn expressions<F: FnMut(&Self::Input) -> bool>(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+        terminator: F,
+    ) -> iter::PrattIter<'_, Self, Inputs, P, F> {
+        iter::PrattIter::new(self, inputs.into_iter(), terminator)
+    }
+
+    /// Parses a list of expressions separated by inputs for which
+    /// `separator` returns true, e.g. the arguments `a, b, c`.
+    ///
+    /// The list ends where `inputs` do, or in front of an input which
+    /// `classify` rejects, and may be empty. A separator after the last
+    /// expression is accepted only if `trailing` is true, and otherwise
+    /// results in `EmptyInput`.
+    #[cfg(feature = "alloc")]
+    fn parse_all<F: FnMut(&Self::Input) -> bool>(
+        &mut self,
+        inputs: &mut core::iter::Peekable<Inputs>,
+        mut separator: F,
+        trailing: bool,
+    ) -> core::result::Result<
+        alloc::vec::Vec<Self::Output>,
+        PrattError<Self::Input, Self::Error, Self::Position>,
+    > {
+        let mut outputs = alloc::vec::Vec::new();
+        // One tail for all items, so that the classification of the first
+        // input of an item is kept for parsing it
+        let mut tail = Tail::new(inputs);
+        loop {
+            let end = match tail.peek() {
+                Some(next) if separator(next) => false,
+                Some(_) => classify(self, &mut tail, P::MIN)
+                    .map_err(PrattError::UserError)?
+                    .is_none(),
+                None => true,
+            };
+            if end && (outputs.is_empty() || trailing) {
+                return Ok(outputs);
+            }
+            outputs.push(self.parse_input(&mut tail.until(&mut separator), P::MIN)?);
+            if tail.next_if(&mut separator).is_none() {
+                return Ok(outputs);
+            }
+        }
+    }
+
+    /// Parses like `parse`, but keeps pending operators in a heap-allocated
+    /// stack instead of recursing, so deeply nested input such as a long
+    /// chain of prefix operators cannot overflow the call stack. The result
+    /// is identical to `parse`, and like `parse` it takes the input after
+    /// the expression from `inputs`, but overridden `nud`/`led`/`parse_input`
+    /// methods are not called.
+    #[cfg(feature = "alloc")]
+    fn parse_iterative(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        iterative::parse(self, &mut Tail::new(&mut inputs.into_iter().peekable()))
+    }
+
+    /// Parses like `parse`, but returns every error that `recover` produced
+    /// a placeholder node for instead of only the first error. The output is
+    /// `None` if the parse failed on an error which could not be recovered
+    /// from, which then comes last. Overridden `nud`/`led`/`parse_input`
+    /// methods are not called.
+    #[cfg(feature = "alloc")]
+    fn parse_all_errors(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> (
+        Option<Self::Output>,
+        alloc::vec::Vec<PrattError<Self::Input, Self::Error, Self::Position>>,
+    )
+    where
+        Self::Input: Clone,
+        Self::Error: Clone,
+        Self::Position: Clone,
+    {
+        let mut parser = collect::Collect::new(self);
+        match parser.parse(inputs) {
+            Ok(node) => (Some(node), parser.errors),
+            Err(error) => {
+                parser.errors.push(error);
+                (None, parser.errors)
+            }
+        }
+    }
+
+    /// Looks for two operators among `samples`, e.g. one input for each
+    /// operator of the grammar, which follow an operand with the same
+    /// precedence but a different associativity, e.g. a left-associative
+    /// `+` and a right-associative `-`. An expression which mixes them is
+    /// grouped depending on which comes first, which is rarely intended.
+    /// Returns the first such pair, in the order of `samples`.
+    #[cfg(feature = "alloc")]
+    fn check_associativity(
+        &mut self,
+        samples: impl IntoIterator<Item = Self::Input>,
+    ) -> core::result::Result<Option<(Self::Input, Self::Input)>, Self::Error> {
+        let mut seen: alloc::vec::Vec<(Self::Input, P, Associativity)> = alloc::vec::Vec::new();
+        for sample in samples {
+            let Some((precedence, associativity)) =
+                self.query_led(&sample)?.and_then(Affix::binary)
+            else {
+                continue;
+            };
+            let precedence = self.normalize(precedence);
+            let conflict = seen
+                .iter()
+                .position(|&(_, p, a)| p == precedence && a != associativity);
+            if let Some(i) = conflict {
+                return Ok(Some((seen.swap_remove(i).0, sample)));
+            }
+            seen.push((sample, precedence, associativity));
+        }
+        Ok(None)
+    }
+
+    /// Checks that `inputs` form an expression like `parse` does, failing
+    /// with the same syntax errors, but without calling the callbacks which
+    /// build the output, so that nothing is built. Errors which only the
+    /// callbacks would raise are therefore not found, `recover` is not
+    /// called, and overridden `nud`/`led`/`parse_input` methods are not
+    /// called either. Neither are `primary` and `postfix_with`, so inputs
+    /// which they would consume are parsed as operators instead, and an
+    /// operator classified as `Affix::PostfixWith` fails with
+    /// `PrattError::UnsupportedAffix`.
+    fn validate(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> core::result::Result<(), PrattError<Self::Input, Self::Error, Self::Position>> {
+        validate::Validate::new(self).parse(inputs)
+    }
+
+    /// Parses an expression from `tail` whose operators all have a left
+    /// binding power above `rbp`, the right binding power of the operator
+    /// whose operand is being parsed. Prefer `parse_with_min_precedence`
+    /// outside of callbacks.
     fn parse_input(
         &mut self,
-        tail: &mut core::iter::Peekable<Inputs>,
-        rbp: Precedence,
-    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error>> {
-        if let Some(head) = tail.next() {
-            let info = self.query(&head).map_err(PrattError::UserError)?;
-            let mut nbp = self.nbp(info);
-            let mut node = self.nud(head, tail, info);
-            while let Some(head) = tail.peek() {
-                let info = self.query(head).map_err(PrattError::UserError)?;
-                let lbp = self.lbp(info);
-                if rbp < lbp && lbp < nbp {
-                    let head = tail.next().unwrap();
-                    nbp = self.nbp(info);
-                    node = self.led(head, tail, info, node?);
-                } else {
-                    break;
-                }
-            }
-            node
-        } else {
-            Err(PrattError::EmptyInput)
+        tail: &mut Tail<'_, Inputs, P>,
+        rbp: P,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        match expression(self, tail, rbp)? {
+            Some(node) => Ok(node),
+            None => empty(self, tail),
         }
     }
 
-    /// Null-Denotation
+    /// Null-Denotation: constructs the expression which starts with `head`,
+    /// an input in operand position which has been classified as `info` and
+    /// taken from `tail`. Any operand of `head` is parsed from `tail`, e.g. at
+    /// the right binding power of a prefix operator, and the expression ends
+    /// in front of the first input that cannot be part of it.
+    ///
+    /// Override this to parse some inputs differently, and call [`nud`] for
+    /// the others, which is what this does by default. Only the parses which
+    /// recurse through `self`, i.e. `parse_input` and the methods built on
+    /// it such as `parse`, call an override: `parse_iterative`,
+    /// `parse_all_errors` and `validate` parse every input with [`nud`].
     fn nud(
         &mut self,
         head: Self::Input,
-        tail: &mut core::iter::Peekable<Inputs>,
-        info: Affix,
-    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error>> {
-        match info {
-            Affix::Prefix(precedence) => {
-                let rhs = self.parse_input(tail, precedence.normalize().lower());
-                self.prefix(head, rhs?).map_err(PrattError::UserError)
-            }
-            Affix::Nilfix => self.primary(head).map_err(PrattError::UserError),
-            Affix::Postfix(_) => Err(PrattError::UnexpectedPostfix(head)),
-            Affix::Infix(_, _) => Err(PrattError::UnexpectedInfix(head)),
-        }
+        tail: &mut Tail<'_, Inputs, P>,
+        info: Affix<P>,
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        nud(self, head, tail, info)
     }
 
-    /// Left-Denotation
+    /// Left-Denotation: extends `lhs` with `head`, an input which follows a
+    /// complete operand and has been classified as `info` by `query_led` and
+    /// taken from `tail`. Any right operand of `head` is parsed from `tail`
+    /// at the right binding power of `info`, see `Affix::rbp`. The parser
+    /// then looks for further operators after the returned expression.
+    ///
+    /// Override this to parse some inputs differently, and call [`led`] for
+    /// the others, which is what this does by default. Like an override of
+    /// `nud`, it is only called by `parse_input` and the methods built on
+    /// it: `parse_iterative`, `parse_all_errors` and `validate` parse every
+    /// input with [`led`].
     fn led(
         &mut self,
         head: Self::Input,
-        tail: &mut core::iter::Peekable<Inputs>,
-        info: Affix,
+        tail: &mut Tail<'_, Inputs, P>,
+        info: Affix<P>,
         lhs: Self::Output,
-    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error>> {
-        match info {
-            Affix::Infix(precedence, associativity) => {
-                let precedence = precedence.normalize();
-                let rhs = match associativity {
-                    Associativity::Left => self.parse_input(tail, precedence),
-                    Associativity::Right => self.parse_input(tail, precedence.lower()),
-                    Associativity::Neither => self.parse_input(tail, precedence.raise()),
-                };
-                self.infix(lhs, head, rhs?).map_err(PrattError::UserError)
-            }
-            Affix::Postfix(_) => self.postfix(lhs, head).map_err(PrattError::UserError),
-            Affix::Nilfix => Err(PrattError::UnexpectedNilfix(head)),
-            Affix::Prefix(_) => Err(PrattError::UnexpectedPrefix(head)),
-        }
+    ) -> core::result::Result<Self::Output, PrattError<Self::Input, Self::Error, Self::Position>>
+    {
+        led(self, head, tail, info, lhs)
     }
 
-    //         <lbp>  <rbp>  <nbp> <kind>
-    // Nilfix:  MIN |  MIN |  MAX | nud
-    // Prefix:  MIN |   bp |  MAX | nud
-    // Postfix:  bp |  MIN |  MAX | led
-    // InfixL:   bp |   bp | bp+1 | led
-    // InfixR:   bp | bp-1 | bp+1 | led
-    // InfixN:   bp |   bp |   bp | led
+    /// Maps a user precedence to the binding power used by the parser.
+    ///
+    /// Defaults to `BindingPower::normalize`. Override this to change how
+    /// far apart adjacent precedences are spread, e.g. to multiply by 1000
+    /// instead of 10 for grammars with densely packed operator tables.
+    fn normalize(&self, precedence: P) -> P {
+        precedence.normalize()
+    }
 
-    /// Left-Binding-Power
-    fn lbp(&mut self, info: Affix) -> Precedence {
-        match info {
-            Affix::Nilfix => Precedence::min(),
-            Affix::Prefix(_) => Precedence::min(),
-            Affix::Postfix(precedence) => precedence.normalize(),
-            Affix::Infix(precedence, _) => precedence.normalize(),
+    /// Right-Binding-Power of an infix operator
+    fn rbp(&self, precedence: P, associativity: Associativity) -> P {
+        let precedence = self.normalize(precedence);
+        match associativity {
+            Associativity::Left => precedence,
+            Associativity::Right => precedence.lower(),
+            Associativity::Neither => precedence.raise(),
         }
     }
 
-    /// Next-Binding-Power
-    fn nbp(&mut self, info: Affix) -> Precedence {
-        match info {
-            Affix::Nilfix => Precedence::max(),
-            Affix::Prefix(_) => Precedence::max(),
-            Affix::Postfix(_) => Precedence::max(),
-            Affix::Infix(precedence, Associativity::Left) => precedence.normalize().raise(),
-            Affix::Infix(precedence, Associativity::Right) => precedence.normalize().raise(),
-            Affix::Infix(precedence, Associativity::Neither) => precedence.normalize(),
+    /// Left-Binding-Power, see `Affix::lbp`
+    fn lbp(&mut self, info: Affix<P>) -> P {
+        info.lbp_with(|precedence| self.normalize(precedence))
+    }
+
+    /// Next-Binding-Power, see `Affix::nbp`
+    fn nbp(&mut self, info: Affix<P>) -> P {
+        info.nbp_with(|precedence| self.normalize(precedence))
+    }
+
+    /// The binding powers `(lbp, rbp, nbp)` of `input` as classified by
+    /// `query`, after `normalize`, e.g. to test an operator table without
+    /// parsing. `rbp` is `None` for an operator without a right operand, see
+    /// `Affix::rbp`.
+    fn binding_powers(
+        &mut self,
+        input: &Self::Input,
+    ) -> core::result::Result<(P, Option<P>, P), Self::Error> {
+        let info = self.query(input)?;
+        let rbp = info.rbp_with(|precedence| self.normalize(precedence));
+        Ok((self.lbp(info), rbp, self.nbp(info)))
+    }
+}
+
+/// The default implementation of [`PrattParser::nud`], for overrides which
+/// only handle some inputs themselves.
+pub fn nud<T, Inputs, P>(
+    parser: &mut T,
+    head: T::Input,
+    tail: &mut Tail<'_, Inputs, P>,
+    info: Affix<P>,
+) -> core::result::Result<T::Output, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match info {
+        Affix::Prefix(precedence) => {
+            let rbp = parser.normalize(precedence).lower();
+            let (head, rhs) = operand(parser, tail, rbp, head)?;
+            parser.prefix(head, rhs).map_err(PrattError::UserError)
+        }
+        Affix::PrefixPrimary(precedence) => {
+            let rbp = parser.normalize(precedence).lower();
+            let chained = classify(parser, tail, rbp).map_err(PrattError::UserError)?;
+            if let Some(Affix::Prefix(_) | Affix::PrefixPrimary(_)) = chained {
+                let next = tail.next().unwrap();
+                let position = parser.position(&next);
+                return recover(parser, PrattError::UnexpectedPrefix(next, position), tail);
+            }
+            let (head, rhs) = operand(parser, tail, rbp, head)?;
+            parser.prefix(head, rhs).map_err(PrattError::UserError)
         }
+        Affix::Nilfix => parser.primary(head, tail).map_err(PrattError::UserError),
+        Affix::Circumfix => {
+            let mut closes = |input: &T::Input| T::closes(&head, input);
+            let (head, inner) = match descend(parser, &mut tail.until(&mut closes), P::MIN)? {
+                Some(inner) => (head, inner),
+                None => missing_operand(parser, head, tail)?,
+            };
+            match tail.next_if(|input| T::closes(&head, input)) {
+                Some(close) => parser.circumfix(head, inner, close),
+                None => {
+                    let position = parser.position(&head);
+                    let error = PrattError::UnclosedDelimiter(head, position);
+                    recover(parser, error, tail)
+                }
+            }
+        }
+        #[cfg(feature = "alloc")]
+        Affix::Mixfix(precedence, separators) => {
+            let mut parts = alloc::vec::Vec::new();
+            let mut keyword = head;
+            for _ in 0..separators {
+                let mut closes = |input: &T::Input| T::closes(&keyword, input);
+                let operand = match descend(parser, &mut tail.until(&mut closes), P::MIN)? {
+                    Some(operand) => operand,
+                    None => {
+                        let (op, operand) = missing_operand(parser, keyword, tail)?;
+                        keyword = op;
+                        operand
+                    }
+                };
+                match tail.next_if(|input| T::closes(&keyword, input)) {
+                    Some(separator) => {
+                        let keyword = core::mem::replace(&mut keyword, separator);
+                        parts.push(MixfixPart::Keyword(keyword));
+                        parts.push(MixfixPart::Operand(operand));
+                    }
+                    None => {
+                        let position = parser.position(&keyword);
+                        let error = PrattError::UnclosedDelimiter(keyword, position);
+                        return recover(parser, error, tail);
+                    }
+                }
+            }
+            let rbp = parser.normalize(precedence).lower();
+            let (keyword, operand) = operand(parser, tail, rbp, keyword)?;
+            parts.push(MixfixPart::Keyword(keyword));
+            parts.push(MixfixPart::Operand(operand));
+            parser.mixfix(parts)
+        }
+        Affix::Postfix(_) | Affix::PostfixWith(_) => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedPostfix(head, position), tail)
+        }
+        Affix::Infix(_, _) | Affix::PostfixOrInfix(_, _) | Affix::Ternary(_, _) => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedInfix(head, position), tail)
+        }
+        #[cfg(feature = "alloc")]
+        Affix::InfixChain(_) => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedInfix(head, position), tail)
+        }
+    }
+}
+
+/// The default implementation of [`PrattParser::led`], for overrides which
+/// only handle some inputs themselves.
+pub fn led<T, Inputs, P>(
+    parser: &mut T,
+    head: T::Input,
+    tail: &mut Tail<'_, Inputs, P>,
+    info: Affix<P>,
+    lhs: T::Output,
+) -> core::result::Result<T::Output, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match info {
+        Affix::Infix(precedence, associativity) => {
+            let rbp = parser.rbp(precedence, associativity);
+            let (head, rhs) = operand(parser, tail, rbp, head)?;
+            parser.infix(lhs, head, rhs).map_err(PrattError::UserError)
+        }
+        #[cfg(feature = "alloc")]
+        Affix::InfixChain(precedence) => {
+            let rbp = parser.rbp(precedence, Associativity::Left);
+            let mut rest = alloc::vec::Vec::new();
+            let mut op = head;
+            loop {
+                rest.push(operand(parser, tail, rbp, op)?);
+                match query_led(parser, tail, rbp).map_err(PrattError::UserError)? {
+                    Some(Affix::InfixChain(next)) if next == precedence => {
+                        op = tail.next().unwrap();
+                    }
+                    _ => break,
+                }
+            }
+            parser.infix_chain(lhs, rest).map_err(PrattError::UserError)
+        }
+        Affix::Ternary(precedence, associativity) => {
+            let mut closes = |input: &T::Input| T::closes(&head, input);
+            let (head, then) = match descend(parser, &mut tail.until(&mut closes), P::MIN)? {
+                Some(then) => (head, then),
+                None => missing_operand(parser, head, tail)?,
+            };
+            let delimiter = match tail.next_if(|input| T::closes(&head, input)) {
+                Some(delimiter) => delimiter,
+                None => {
+                    let position = parser.position(&head);
+                    let error = PrattError::UnclosedDelimiter(head, position);
+                    return recover(parser, error, tail);
+                }
+            };
+            let rbp = parser.rbp(precedence, associativity);
+            let (delimiter, els) = operand(parser, tail, rbp, delimiter)?;
+            parser.ternary(lhs, head, then, delimiter, els)
+        }
+        Affix::Postfix(_) => parser.postfix(lhs, head).map_err(PrattError::UserError),
+        Affix::PostfixOrInfix(_, _) => {
+            let info = resolve(parser, tail, info).map_err(PrattError::UserError)?;
+            led(parser, head, tail, info, lhs)
+        }
+        Affix::PostfixWith(_) => parser.postfix_with(lhs, head, tail),
+        Affix::Nilfix | Affix::Circumfix => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedNilfix(head, position), tail)
+        }
+        Affix::Prefix(_) | Affix::PrefixPrimary(_) => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedPrefix(head, position), tail)
+        }
+        #[cfg(feature = "alloc")]
+        Affix::Mixfix(_, _) => {
+            let position = parser.position(&head);
+            recover(parser, PrattError::UnexpectedPrefix(head, position), tail)
+        }
+    }
+}
+
+/// Parses a nested operand, one level deeper than the operator in `tail`.
+fn descend<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    rbp: P,
+) -> core::result::Result<Option<T::Output>, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    if parser.max_depth().is_some_and(|max| tail.depth >= max) {
+        return Err(PrattError::DepthExceeded);
+    }
+    tail.depth += 1;
+    let node = expression(parser, tail, rbp);
+    tail.depth -= 1;
+    node
+}
+
+/// Parses the operand after `op` like `descend`, and hands `op` back with
+/// it. A missing operand is reported as `PrattError::MissingOperand`.
+fn operand<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    rbp: P,
+    op: T::Input,
+) -> core::result::Result<(T::Input, T::Output), PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match descend(parser, tail, rbp)? {
+        Some(node) => Ok((op, node)),
+        None => missing_operand(parser, op, tail),
+    }
+}
+
+/// Reports that the operand after `op` is missing. If the parser recovers,
+/// `op` is handed back with the node which stands in for the operand.
+fn missing_operand<T, Inputs, P>(
+    parser: &mut T,
+    op: T::Input,
+    tail: &mut Tail<'_, Inputs, P>,
+) -> core::result::Result<(T::Input, T::Output), PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let position = parser.position(&op);
+    let error = PrattError::MissingOperand(op, position);
+    match (parser.recover(&error, tail), error) {
+        (Some(node), PrattError::MissingOperand(op, _)) => Ok((op, node)),
+        (_, error) => Err(error),
+    }
+}
+
+/// Parses an expression like `PrattParser::parse_input`, or returns `None`
+/// if it is empty.
+fn expression<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    rbp: P,
+) -> core::result::Result<Option<T::Output>, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let Some(info) = classify(parser, tail, rbp).map_err(PrattError::UserError)? else {
+        return Ok(None);
+    };
+    let head = tail.next().unwrap();
+    // The operator which ends the operand so far
+    let mut last = info;
+    let mut node = parser.nud(head, tail, info);
+    while let Some(info) = query_led(parser, tail, rbp).map_err(PrattError::UserError)? {
+        if let (true, Some(precedence)) = (info.starts_operand(), parser.juxtaposition()) {
+            // Adjacent operands, as if joined by a left-associative operator
+            let lbp = parser.normalize(precedence);
+            let next = classify(parser, tail, lbp).map_err(PrattError::UserError)?;
+            if !(rbp < lbp && follows(parser, last, lbp)) || next.is_none() {
+                break;
+            }
+            let lhs = node?;
+            let rhs = descend(parser, tail, lbp)?.unwrap();
+            last = Affix::Infix(precedence, Associativity::Left);
+            node = parser.juxtapose(lhs, rhs);
+            continue;
+        }
+        let lbp = parser.lbp(info);
+        if rbp < lbp && follows(parser, last, lbp) {
+            let head = tail.next().unwrap();
+            last = resolve(parser, tail, info).map_err(PrattError::UserError)?;
+            node = parser.led(head, tail, last, node?);
+        } else if rbp < lbp && lbp == parser.nbp(last) {
+            let _lhs = node?;
+            let head = tail.next().unwrap();
+            let position = parser.position(&head);
+            node = recover(parser, PrattError::NonAssociative(head, position), tail);
+        } else {
+            break;
+        }
+    }
+    node.map(Some)
+}
+
+/// Returns true if an operator with the left binding power `lbp` may extend
+/// the operand which `last` ends. The next binding power of `last` is only
+/// an upper bound: it equals `lbp` both for a non-associative `last` of the
+/// same precedence and where `raise` saturates, e.g. at `Precedence::max()`,
+/// so the associativity of `last` tells them apart.
+pub(crate) fn follows<T, Inputs, P>(parser: &mut T, last: Affix<P>, lbp: P) -> bool
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let nbp = parser.nbp(last);
+    lbp < nbp || (lbp == nbp && !last.non_associative())
+}
+
+/// Classifies the next input of `tail` with `PrattParser::classify`, or
+/// returns `None` if there is none.
+fn classify<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    rbp: P,
+) -> core::result::Result<Option<Affix<P>>, T::Error>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match tail.peek() {
+        Some(_) => lookup(parser, tail, Denotation::Nud, rbp),
+        None => Ok(None),
+    }
+}
+
+/// Classifies the next input of `tail` with `PrattParser::query_led`, or
+/// returns `None` if there is none.
+fn query_led<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    rbp: P,
+) -> core::result::Result<Option<Affix<P>>, T::Error>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match tail.peek() {
+        Some(_) => lookup(parser, tail, Denotation::Led, rbp),
+        None => Ok(None),
+    }
+}
+
+/// Classifies the next input of `tail`, which must exist, in the position
+/// `denotation` of an expression parsed at `rbp`. The sentinel of `tail` is
+/// not checked. The result is kept in `tail` until the input is consumed.
+pub(crate) fn lookup<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    denotation: Denotation,
+    rbp: P,
+) -> core::result::Result<Option<Affix<P>>, T::Error>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let seen = tail.memo.get();
+    let cache = match denotation {
+        Denotation::Led => &mut seen.led,
+        Denotation::Nud => &mut seen.nud,
+    };
+    if cache.is_none() {
+        let mut context = QueryContext {
+            inputs: tail.inputs,
+            denotation,
+            rbp,
+        };
+        *cache = Some(parser.query_with_context(&mut context)?);
+    }
+    Ok(cache.flatten())
+}
+
+/// Decides what an `Affix::PostfixOrInfix` operator is by the next input of
+/// `tail`, see `Affix::resolve`. Other affixes are returned as they are.
+fn resolve<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+    info: Affix<P>,
+) -> core::result::Result<Affix<P>, T::Error>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    let Affix::PostfixOrInfix(precedence, associativity) = info else {
+        return Ok(info);
+    };
+    let rbp = parser.rbp(precedence, associativity);
+    Ok(info.resolve(classify(parser, tail, rbp)?))
+}
+
+/// Stands in for an expression which is empty as a whole, see
+/// `PrattParser::on_empty`.
+fn empty<T, Inputs, P>(
+    parser: &mut T,
+    tail: &mut Tail<'_, Inputs, P>,
+) -> core::result::Result<T::Output, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match parser.on_empty().map_err(PrattError::UserError)? {
+        Some(node) => Ok(node),
+        None => recover(parser, PrattError::EmptyInput, tail),
+    }
+}
+
+/// Lets `parser` recover from `error` where it is raised.
+fn recover<T, Inputs, P>(
+    parser: &mut T,
+    error: PrattError<T::Input, T::Error, T::Position>,
+    tail: &mut Tail<'_, Inputs, P>,
+) -> core::result::Result<T::Output, PrattError<T::Input, T::Error, T::Position>>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    match parser.recover(&error, tail) {
+        Some(node) => Ok(node),
+        None => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::boxed::Box;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    #[derive(Debug, Eq, PartialEq)]
+    enum Expr {
+        BinOp(Box<Expr>, char, Box<Expr>),
+        UnOp(char, Box<Expr>),
+        Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+        Index(Box<Expr>, Box<Expr>),
+        #[cfg(feature = "alloc")]
+        Chain(Box<Expr>, Vec<(char, Expr)>),
+        #[cfg(feature = "alloc")]
+        Mixfix(Vec<(char, Expr)>),
+        Int(u32),
+        Error,
+    }
+
+    /// Tokens are characters paired with their byte offset in the source.
+    type Token = (usize, char);
+
+    struct ExprParser;
+
+    impl<I> PrattParser<I> for ExprParser
+    where
+        I: Iterator<Item = Token>,
+    {
+        type Error = NoError;
+        type Input = Token;
+        type Output = Expr;
+        type Position = usize;
+
+        fn query(&mut self, &(_, c): &Token) -> Result<Affix> {
+            let affix = match c {
+                '=' | '<' => Affix::Infix(Precedence(2), Associativity::Neither),
+                '+' | '-' => Affix::Infix(Precedence(3), Associativity::Left),
+                #[cfg(feature = "alloc")]
+                '&' => Affix::InfixChain(Precedence(3)),
+                '*' | '/' => Affix::Infix(Precedence(4), Associativity::Left),
+                '%' => Affix::PostfixOrInfix(Precedence(4), Associativity::Left),
+                '?' => Affix::Ternary(Precedence(1), Associativity::Right),
+                '$' => Affix::Postfix(Precedence(5)),
+                '[' => Affix::PostfixWith(Precedence(8)),
+                '.' => Affix::PostfixWith(Precedence(9)),
+                'n' => Affix::PostfixWith(Precedence(2)),
+                '!' => Affix::Prefix(Precedence(6)),
+                '~' => Affix::Prefix(Precedence(8)),
+                'r' => Affix::Prefix(Precedence(1)),
+                't' => Affix::PrefixPrimary(Precedence(6)),
+                #[cfg(feature = "alloc")]
+                'f' => Affix::Mixfix(Precedence(1), 2),
+                'h' | 'e' => Affix::Nilfix,
+                '^' => Affix::Infix(Precedence(7), Associativity::Right),
+                '(' | '|' | '⌈' => Affix::Circumfix,
+                ')' | ']' | ':' | '⌉' => Affix::Nilfix,
+                '0'..='9' => Affix::Nilfix,
+                _ => unreachable!(),
+            };
+            Ok(affix)
+        }
+
+        fn classify(&mut self, input: &Token) -> Result<Option<Affix>> {
+            match input {
+                (_, ';') => Ok(None),
+                _ => PrattParser::<I>::query(self, input).map(Some),
+            }
+        }
+
+        fn query_led(&mut self, input: &Token) -> Result<Option<Affix>> {
+            match input {
+                (_, '~') => Ok(Some(Affix::Infix(Precedence(3), Associativity::Left))),
+                #[cfg(feature = "alloc")]
+                (_, '|') => Ok(Some(Affix::InfixChain(Precedence(3)))),
+                _ => PrattParser::<I>::classify(self, input),
+            }
+        }
+
+        fn position(&self, &(offset, _): &Token) -> usize {
+            offset
+        }
+
+        fn primary(&mut self, (_, c): Token, _: &mut Tail<'_, I>) -> Result<Expr> {
+            Ok(Expr::Int(c.to_digit(10).unwrap()))
+        }
+
+        fn infix(&mut self, lhs: Expr, (_, op): Token, rhs: Expr) -> Result<Expr> {
+            Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+        }
+
+        #[cfg(feature = "alloc")]
+        fn infix_chain(&mut self, head: Expr, rest: Vec<(Token, Expr)>) -> Result<Expr> {
+            let rest = rest.into_iter().map(|((_, op), rhs)| (op, rhs)).collect();
+            Ok(Expr::Chain(Box::new(head), rest))
+        }
+
+        #[cfg(feature = "alloc")]
+        fn mixfix(
+            &mut self,
+            parts: Vec<MixfixPart<Token, Expr>>,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            let mut pairs = Vec::new();
+            let mut parts = parts.into_iter();
+            while let (
+                Some(MixfixPart::Keyword((_, keyword))),
+                Some(MixfixPart::Operand(operand)),
+            ) = (parts.next(), parts.next())
+            {
+                pairs.push((keyword, operand));
+            }
+            Ok(Expr::Mixfix(pairs))
+        }
+
+        fn prefix(&mut self, (_, op): Token, rhs: Expr) -> Result<Expr> {
+            Ok(Expr::UnOp(op, Box::new(rhs)))
+        }
+
+        fn postfix(&mut self, lhs: Expr, (_, op): Token) -> Result<Expr> {
+            Ok(Expr::UnOp(op, Box::new(lhs)))
+        }
+
+        fn ternary(
+            &mut self,
+            cond: Expr,
+            _: Token,
+            then: Expr,
+            _: Token,
+            els: Expr,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            Ok(Expr::Ternary(Box::new(cond), Box::new(then), Box::new(els)))
+        }
+
+        fn postfix_with(
+            &mut self,
+            lhs: Expr,
+            op: Token,
+            tail: &mut Tail<'_, I>,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            if op.1 == '.' {
+                // The field of a member access is a single digit
+                return match tail.next() {
+                    Some((_, field @ '0'..='9')) => Ok(Expr::BinOp(
+                        Box::new(lhs),
+                        '.',
+                        Box::new(Expr::Int(field.to_digit(10).unwrap())),
+                    )),
+                    _ => Err(PrattError::UserError(NoError)),
+                };
+            }
+            if op.1 == 'n' {
+                // `n i` is a two-input infix operator, like `not in`
+                if tail.next_if(|&(_, c)| c == 'i').is_none() {
+                    return Err(PrattError::UnexpectedPostfix(op, op.0));
+                }
+                let rbp = PrattParser::<I>::rbp(self, Precedence(2), Associativity::Left);
+                let rhs = self.parse_input(tail, rbp)?;
+                return Ok(Expr::BinOp(Box::new(lhs), 'n', Box::new(rhs)));
+            }
+            let mut stop = |&(_, c): &Token| c == ']';
+            let index = self.parse_input(&mut tail.until(&mut stop), Precedence::MIN);
+            match (index, tail.next_if(|&(_, c)| c == ']')) {
+                (Ok(index), Some(_)) => Ok(Expr::Index(Box::new(lhs), Box::new(index))),
+                _ => Err(PrattError::UserError(NoError)),
+            }
+        }
+
+        fn circumfix(
+            &mut self,
+            (_, open): Token,
+            inner: Expr,
+            _: Token,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            match open {
+                '(' => Ok(inner),
+                _ => Ok(Expr::UnOp(open, Box::new(inner))),
+            }
+        }
+
+        fn closes(&(_, op): &Token, &(_, delimiter): &Token) -> bool {
+            matches!(
+                (op, delimiter),
+                ('?', ':') | ('(', ')') | ('|', '|') | ('⌈', '⌉') | ('f', 'h') | ('h', 'e')
+            )
+        }
+    }
+
+    /// Fractional precedence levels, so new operators fit between old ones.
+    #[derive(Copy, Clone)]
+    struct Fractional(f64);
+
+    impl PartialEq for Fractional {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other).is_eq()
+        }
+    }
+
+    impl Eq for Fractional {}
+
+    impl PartialOrd for Fractional {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Fractional {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.0.total_cmp(&other.0)
+        }
+    }
+
+    impl BindingPower for Fractional {
+        const MIN: Fractional = Fractional(f64::NEG_INFINITY);
+        const MAX: Fractional = Fractional(f64::INFINITY);
+        fn normalize(self) -> Fractional {
+            self
+        }
+        fn raise(self) -> Fractional {
+            Fractional(self.0.next_up())
+        }
+        fn lower(self) -> Fractional {
+            Fractional(self.0.next_down())
+        }
+    }
+
+    struct FractionalParser;
+
+    impl<I> PrattParser<I, Fractional> for FractionalParser
+    where
+        I: Iterator<Item = Token>,
+    {
+        type Error = NoError;
+        type Input = Token;
+        type Output = Expr;
+        type Position = ();
+
+        fn query(&mut self, &(_, c): &Token) -> Result<Affix<Fractional>> {
+            let affix = match c {
+                '+' => Affix::Infix(Fractional(0.5), Associativity::Left),
+                '*' => Affix::Infix(Fractional(0.75), Associativity::Left),
+                '0'..='9' => Affix::Nilfix,
+                _ => unreachable!(),
+            };
+            Ok(affix)
+        }
+
+        fn primary(&mut self, (_, c): Token, _: &mut Tail<'_, I, Fractional>) -> Result<Expr> {
+            Ok(Expr::Int(c.to_digit(10).unwrap()))
+        }
+
+        fn infix(&mut self, lhs: Expr, (_, op): Token, rhs: Expr) -> Result<Expr> {
+            Ok(Expr::BinOp(Box::new(lhs), op, Box::new(rhs)))
+        }
+
+        fn prefix(&mut self, (_, op): Token, rhs: Expr) -> Result<Expr> {
+            Ok(Expr::UnOp(op, Box::new(rhs)))
+        }
+
+        fn postfix(&mut self, lhs: Expr, (_, op): Token) -> Result<Expr> {
+            Ok(Expr::UnOp(op, Box::new(lhs)))
+        }
+    }
+
+    /// The hooks of `ExprParser` which a test replaces through `Custom`. Each
+    /// one does what `ExprParser` does unless it is overridden.
+    trait Hooks<I: Iterator<Item = Token>> {
+        fn query(&mut self, input: &Token) -> Result<Affix> {
+            PrattParser::<I>::query(&mut ExprParser, input)
+        }
+
+        fn query_led(&mut self, input: &Token) -> Result<Option<Affix>> {
+            PrattParser::<I>::query_led(&mut ExprParser, input)
+        }
+
+        fn primary(&mut self, input: Token, tail: &mut Tail<'_, I>) -> Result<Expr> {
+            ExprParser.primary(input, tail)
+        }
+
+        fn infix(&mut self, lhs: Expr, op: Token, rhs: Expr) -> Result<Expr> {
+            PrattParser::<I>::infix(&mut ExprParser, lhs, op, rhs)
+        }
+
+        fn prefix(&mut self, op: Token, rhs: Expr) -> Result<Expr> {
+            PrattParser::<I>::prefix(&mut ExprParser, op, rhs)
+        }
+
+        fn postfix(&mut self, lhs: Expr, op: Token) -> Result<Expr> {
+            PrattParser::<I>::postfix(&mut ExprParser, lhs, op)
+        }
+
+        fn recover(
+            &mut self,
+            _: &PrattError<Token, NoError, usize>,
+            _: &mut Tail<'_, I>,
+        ) -> Option<Expr> {
+            None
+        }
+
+        fn on_empty(&mut self) -> Result<Option<Expr>> {
+            Ok(None)
+        }
+
+        fn max_depth(&self) -> Option<usize> {
+            None
+        }
+
+        fn normalize(&self, precedence: Precedence) -> Precedence {
+            precedence.normalize()
+        }
+    }
+
+    /// Same grammar as `ExprParser`, with the hooks of `H` in place of its
+    /// own.
+    struct Custom<H>(H);
+
+    impl<I, H> PrattParser<I> for Custom<H>
+    where
+        I: Iterator<Item = Token>,
+        H: Hooks<I>,
+    {
+        type Error = NoError;
+        type Input = Token;
+        type Output = Expr;
+        type Position = usize;
+
+        fn query(&mut self, input: &Token) -> Result<Affix> {
+            self.0.query(input)
+        }
+
+        fn classify(&mut self, input: &Token) -> Result<Option<Affix>> {
+            match input {
+                (_, ';') => Ok(None),
+                _ => self.0.query(input).map(Some),
+            }
+        }
+
+        fn query_led(&mut self, input: &Token) -> Result<Option<Affix>> {
+            self.0.query_led(input)
+        }
+
+        fn position(&self, input: &Token) -> usize {
+            PrattParser::<I>::position(&ExprParser, input)
+        }
+
+        fn primary(&mut self, input: Token, tail: &mut Tail<'_, I>) -> Result<Expr> {
+            self.0.primary(input, tail)
+        }
+
+        fn infix(&mut self, lhs: Expr, op: Token, rhs: Expr) -> Result<Expr> {
+            self.0.infix(lhs, op, rhs)
+        }
+
+        #[cfg(feature = "alloc")]
+        fn infix_chain(&mut self, head: Expr, rest: Vec<(Token, Expr)>) -> Result<Expr> {
+            PrattParser::<I>::infix_chain(&mut ExprParser, head, rest)
+        }
+
+        #[cfg(feature = "alloc")]
+        fn mixfix(
+            &mut self,
+            parts: Vec<MixfixPart<Token, Expr>>,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            PrattParser::<I>::mixfix(&mut ExprParser, parts)
+        }
+
+        fn prefix(&mut self, op: Token, rhs: Expr) -> Result<Expr> {
+            self.0.prefix(op, rhs)
+        }
+
+        fn postfix(&mut self, lhs: Expr, op: Token) -> Result<Expr> {
+            self.0.postfix(lhs, op)
+        }
+
+        fn ternary(
+            &mut self,
+            cond: Expr,
+            op: Token,
+            then: Expr,
+            delimiter: Token,
+            els: Expr,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            PrattParser::<I>::ternary(&mut ExprParser, cond, op, then, delimiter, els)
+        }
+
+        fn postfix_with(
+            &mut self,
+            lhs: Expr,
+            op: Token,
+            tail: &mut Tail<'_, I>,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            ExprParser.postfix_with(lhs, op, tail)
+        }
+
+        fn circumfix(
+            &mut self,
+            open: Token,
+            inner: Expr,
+            close: Token,
+        ) -> Result<Expr, PrattError<Token, NoError, usize>> {
+            PrattParser::<I>::circumfix(&mut ExprParser, open, inner, close)
+        }
+
+        fn closes(op: &Token, delimiter: &Token) -> bool {
+            <ExprParser as PrattParser<I>>::closes(op, delimiter)
+        }
+
+        fn recover(
+            &mut self,
+            error: &PrattError<Token, NoError, usize>,
+            tail: &mut Tail<'_, I>,
+        ) -> Option<Expr> {
+            self.0.recover(error, tail)
+        }
+
+        fn on_empty(&mut self) -> Result<Option<Expr>> {
+            self.0.on_empty()
+        }
+
+        fn max_depth(&self) -> Option<usize> {
+            self.0.max_depth()
+        }
+
+        fn normalize(&self, precedence: Precedence) -> Precedence {
+            self.0.normalize(precedence)
+        }
+    }
+
+    /// Precedences spread by 1000.
+    struct Dense;
+
+    impl<I: Iterator<Item = Token>> Hooks<I> for Dense {
+        fn normalize(&self, precedence: Precedence) -> Precedence {
+            Precedence(precedence.0.saturating_mul(1000))
+        }
+    }
+
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+    enum Level {
+        Sum,
+        Product,
+        Power,
+    }
+
+    /// Same grammar as `ExprParser`, but with precedences from `Level`.
+    struct OrdinalParser;
+
+    impl<I> PrattParser<I, Ordinal<Level>> for OrdinalParser
+    where
+        I: Iterator<Item = Token>,
+    {
+        type Error = NoError;
+        type Input = Token;
+        type Output = Expr;
+        type Position = ();
+
+        fn query(&mut self, &(_, c): &Token) -> Result<Affix<Ordinal<Level>>> {
+            let affix = match c {
+                '+' | '-' => Affix::Infix(Ordinal::new(Level::Sum), Associativity::Left),
+                '*' | '/' => Affix::Infix(Ordinal::new(Level::Product), Associativity::Left),
+                '^' => Affix::Infix(Ordinal::new(Level::Power), Associativity::Right),
+                '!' | '~' => Affix::Prefix(Ordinal::new(Level::Power)),
+                '0'..='9' => Affix::Nilfix,
+                _ => unreachable!(),
+            };
+            Ok(affix)
+        }
+
+        fn primary(&mut self, (_, c): Token, _: &mut Tail<'_, I, Ordinal<Level>>) -> Result<Expr> {
+            Ok(Expr::Int(c.to_digit(10).unwrap()))
+        }
+
+        fn infix(&mut self, lhs: Expr, op: Token, rhs: Expr) -> Result<Expr> {
+            PrattParser::<I>::infix(&mut ExprParser, lhs, op, rhs)
+        }
+
+        fn prefix(&mut self, op: Token, rhs: Expr) -> Result<Expr> {
+            PrattParser::<I>::prefix(&mut ExprParser, op, rhs)
+        }
+
+        fn postfix(&mut self, lhs: Expr, op: Token) -> Result<Expr> {
+            PrattParser::<I>::postfix(&mut ExprParser, lhs, op)
+        }
+    }
+
+    /// Recovers from all syntax errors.
+    #[derive(Default)]
+    struct Recovering {
+        errors: Vec<String>,
+    }
+
+    impl<I: Iterator<Item = Token>> Hooks<I> for Recovering {
+        fn recover(
+            &mut self,
+            error: &PrattError<Token, NoError, usize>,
+            _: &mut Tail<'_, I>,
+        ) -> Option<Expr> {
+            self.errors.push(error.to_string());
+            Some(Expr::Error)
+        }
+    }
+
+    /// A maximum depth.
+    struct Limited(usize);
+
+    impl<I: Iterator<Item = Token>> Hooks<I> for Limited {
+        fn max_depth(&self) -> Option<usize> {
+            Some(self.0)
+        }
+    }
+
+    /// Same grammar as `ExprParser`, but computes the value of the expression.
+    struct Calculator;
+
+    impl<I> PrattParser<I> for Calculator
+    where
+        I: Iterator<Item = Token>,
+    {
+        type Error = NoError;
+        type Input = Token;
+        type Output = i64;
+        type Position = usize;
+
+        fn query(&mut self, input: &Token) -> Result<Affix> {
+            PrattParser::<I>::query(&mut ExprParser, input)
+        }
+
+        fn primary(&mut self, (_, c): Token, _: &mut Tail<'_, I>) -> Result<i64> {
+            Ok(c.to_digit(10).unwrap() as i64)
+        }
+
+        fn infix(&mut self, lhs: i64, (_, op): Token, rhs: i64) -> Result<i64> {
+            Ok(match op {
+                '=' => (lhs == rhs) as i64,
+                '<' => (lhs < rhs) as i64,
+                '+' => lhs + rhs,
+                '-' => lhs - rhs,
+                '*' => lhs * rhs,
+                '/' => lhs / rhs,
+                '^' => lhs.pow(rhs as u32),
+                '&' => lhs & rhs,
+                '|' => lhs | rhs,
+                _ => unreachable!(),
+            })
+        }
+
+        fn prefix(&mut self, (_, op): Token, rhs: i64) -> Result<i64> {
+            Ok(match op {
+                '!' => (rhs == 0) as i64,
+                '~' => -rhs,
+                _ => unreachable!(),
+            })
+        }
+
+        fn postfix(&mut self, lhs: i64, _: Token) -> Result<i64> {
+            Ok(lhs * lhs)
+        }
+
+        fn ternary(
+            &mut self,
+            cond: i64,
+            _: Token,
+            then: i64,
+            _: Token,
+            els: i64,
+        ) -> Result<i64, PrattError<Token, NoError, usize>> {
+            Ok(if cond != 0 { then } else { els })
+        }
+
+        fn circumfix(
+            &mut self,
+            _: Token,
+            inner: i64,
+            _: Token,
+        ) -> Result<i64, PrattError<Token, NoError, usize>> {
+            Ok(inner)
+        }
+
+        fn closes(op: &Token, delimiter: &Token) -> bool {
+            <ExprParser as PrattParser<I>>::closes(op, delimiter)
+        }
+    }
+
+    fn tokens(input: &str) -> Vec<Token> {
+        input.char_indices().filter(|(_, c)| *c != ' ').collect()
+    }
+
+    fn parse(input: &str) -> core::result::Result<Expr, PrattError<Token, NoError, usize>> {
+        ExprParser.parse(tokens(input))
+    }
+
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::BinOp(lhs, op, rhs) => std::format!("({}{}{})", show(lhs), op, show(rhs)),
+            Expr::UnOp(op, rhs) => std::format!("({}{})", op, show(rhs)),
+            Expr::Ternary(cond, then, els) => {
+                std::format!("({}?{}:{})", show(cond), show(then), show(els))
+            }
+            Expr::Index(lhs, index) => std::format!("{}[{}]", show(lhs), show(index)),
+            #[cfg(feature = "alloc")]
+            Expr::Mixfix(parts) => {
+                let parts = parts
+                    .iter()
+                    .map(|(keyword, operand)| std::format!("{}{}", keyword, show(operand)));
+                std::format!("({})", parts.collect::<String>())
+            }
+            #[cfg(feature = "alloc")]
+            Expr::Chain(head, rest) => {
+                let rest = rest
+                    .iter()
+                    .map(|(op, rhs)| std::format!("{}{}", op, show(rhs)));
+                std::format!("({}{})", show(head), rest.collect::<String>())
+            }
+            Expr::Int(i) => std::format!("{}", i),
+            Expr::Error => String::from("_"),
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(show(&parse("1*2+3^4^5").unwrap()), "((1*2)+(3^(4^5)))");
+    }
+
+    #[test]
+    fn fractional_precedence() {
+        let expr = FractionalParser.parse(tokens("1+2*3+4"));
+        assert_eq!(show(&expr.unwrap()), "((1+(2*3))+4)");
+    }
+
+    #[test]
+    fn parse_until() {
+        let mut inputs = tokens("1+2*3;4").into_iter().peekable();
+        let expr = ExprParser.parse_until(&mut inputs, |&(_, c)| c == ';');
+        assert_eq!(show(&expr.unwrap()), "(1+(2*3))");
+        assert_eq!(inputs.next(), Some((5, ';')));
+
+        let mut inputs = tokens("1+;").into_iter().peekable();
+        let expr = ExprParser.parse_until(&mut inputs, |&(_, c)| c == ';');
+        assert!(matches!(expr, Err(PrattError::MissingOperand((1, '+'), 1))));
+        assert_eq!(inputs.next(), Some((2, ';')));
+    }
+
+    #[test]
+    fn parse_terminated() {
+        // `h` stands for `do`, after the condition of a loop
+        let is_do = |&(_, c): &Token| c == 'h';
+        let mut inputs = tokens("1+2h3").into_iter().peekable();
+        let (expr, terminator) = ExprParser.parse_terminated(&mut inputs, is_do).unwrap();
+        assert_eq!(show(&expr), "(1+2)");
+        assert_eq!(terminator, (3, 'h'));
+        assert_eq!(inputs.next(), Some((4, '3')));
+
+        let mut inputs = tokens("1+2;3").into_iter().peekable();
+        let error = ExprParser.parse_terminated(&mut inputs, is_do).unwrap_err();
+        assert!(matches!(
+            error,
+            PrattError::MissingTerminator(Some(((3, ';'), 3)))
+        ));
+        assert_eq!(error.position(), Some(&3));
+
+        let mut inputs = tokens("1+2").into_iter().peekable();
+        let error = ExprParser.parse_terminated(&mut inputs, is_do).unwrap_err();
+        assert!(matches!(error, PrattError::MissingTerminator(None)));
+        assert_eq!(
+            error.to_string(),
+            "Expected a terminator, found the end of input"
+        );
+    }
+
+    #[test]
+    fn parse_with_min_precedence() {
+        let mut inputs = tokens("!1*2+3").into_iter().peekable();
+        let expr = ExprParser.parse_with_min_precedence(&mut inputs, Precedence::MAX);
+        assert_eq!(show(&expr.unwrap()), "(!1)");
+        assert_eq!(inputs.next(), Some((2, '*')));
+
+        let mut inputs = tokens("1*2+3").into_iter().peekable();
+        let min = Precedence(3).normalize();
+        let expr = ExprParser.parse_with_min_precedence(&mut inputs, min);
+        assert_eq!(show(&expr.unwrap()), "(1*2)");
+        assert_eq!(inputs.next(), Some((3, '+')));
+
+        let mut inputs = tokens("1^2*3").into_iter().peekable();
+        let min = Precedence(4).normalize();
+        let expr = ExprParser.parse_with_min_precedence(&mut inputs, min);
+        assert_eq!(show(&expr.unwrap()), "(1^2)");
+        assert_eq!(inputs.next(), Some((3, '*')));
+    }
+
+    #[test]
+    fn ordinal_precedence() {
+        let expr = OrdinalParser.parse(tokens("1-2+!3*4^5^6-7"));
+        assert_eq!(show(&expr.unwrap()), "(((1-2)+((!3)*(4^(5^6))))-7)");
+    }
+
+    #[test]
+    fn normalize() {
+        for input in ["1=2+3", "1+2-3*4", "!1^2^3$", "1?2?3:4:5", "1*2+3^4^5"] {
+            let dense = Custom(Dense).parse(tokens(input)).unwrap();
+            let sparse = ExprParser.parse(tokens(input)).unwrap();
+            assert_eq!(dense, sparse);
+        }
+    }
+
+    #[test]
+    fn ternary() {
+        assert_eq!(show(&parse("1=2?3+4:5").unwrap()), "((1=2)?(3+4):5)");
+        assert_eq!(show(&parse("1?2?3:4:5").unwrap()), "(1?(2?3:4):5)");
+        assert_eq!(show(&parse("1?2:3?4:5").unwrap()), "(1?2:(3?4:5))");
+        match parse("1?2+3") {
+            Err(e @ PrattError::UnclosedDelimiter((_, '?'), 1)) => {
+                assert_eq!(e.to_string(), "Expected a delimiter closing (1, '?')");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn circumfix() {
+        assert_eq!(show(&parse("(1+2)*3").unwrap()), "((1+2)*3)");
+        assert_eq!(show(&parse("!(1?(2):3)$").unwrap()), "($(!(1?2:3)))");
+        for input in ["(1+2", "(1+2:3)"] {
+            match parse(input) {
+                Err(PrattError::UnclosedDelimiter((_, '('), 0)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn postfix_with() {
+        assert_eq!(show(&parse("1[1+2][3]").unwrap()), "1[(1+2)][3]");
+        assert_eq!(show(&parse("!1[2[0]]^3").unwrap()), "(!(1[2[0]]^3))");
+        assert!(matches!(parse("1[2"), Err(PrattError::UserError(NoError))));
+    }
+
+    #[test]
+    fn context_dependent_precedence() {
+        /// `|` binds weaker than `&` outside of brackets, and tighter inside.
+        #[derive(Default)]
+        struct Shell {
+            nested: bool,
+        }
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Shell {
+            type Error = String;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix, String> {
+                Ok(match c {
+                    '|' if self.nested => Affix::Infix(Precedence(3), Associativity::Left),
+                    '|' => Affix::Infix(Precedence(1), Associativity::Left),
+                    '&' => Affix::Infix(Precedence(2), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, tail: &mut Tail<'_, I>) -> Result<String, String> {
+                if c != '[' {
+                    return Ok(c.into());
+                }
+                let nested = core::mem::replace(&mut self.nested, true);
+                let mut stop = |&c: &char| c == ']';
+                let inner = self.parse_input(&mut tail.until(&mut stop), Precedence::MIN);
+                self.nested = nested;
+                let inner = inner.map_err(|error| std::format!("in brackets: {}", error))?;
+                tail.next_if(|&c| c == ']').ok_or("unclosed bracket")?;
+                Ok(std::format!("[{}]", inner))
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String, String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String, String> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String, String> {
+                unreachable!()
+            }
+        }
+
+        let parse = |input: &str| Shell::default().parse(input.chars());
+        assert_eq!(parse("a|b&c").unwrap(), "(a|(b&c))");
+        assert_eq!(parse("[a|b&c]").unwrap(), "[((a|b)&c)]");
+        assert_eq!(parse("[a|b&c]|d&e").unwrap(), "([((a|b)&c)]|(d&e))");
+        let error = parse("[a|]").unwrap_err().to_string();
+        assert_eq!(
+            error,
+            std::format!(
+                "in brackets: {}",
+                PrattError::<char, String>::MissingOperand('|', ())
+            )
+        );
+        assert!(matches!(parse("[a|b"), Err(PrattError::UserError(e)) if e == "unclosed bracket"));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn query_context() {
+        /// Records where each input is classified.
+        #[derive(Default)]
+        struct Record(Vec<(char, Denotation, u32)>);
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Record {
+            type Error = NoError;
+            type Input = char;
+            type Output = ();
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(1), Associativity::Left),
+                    '*' => Affix::Infix(Precedence(2), Associativity::Left),
+                    '-' => Affix::Prefix(Precedence(3)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn query_with_context(
+                &mut self,
+                context: &mut QueryContext<'_, I>,
+            ) -> Result<Option<Affix>> {
+                let c = *context.input();
+                self.0.push((c, context.denotation(), context.rbp().get()));
+                PrattParser::<I>::query(self, &c).map(Some)
+            }
+
+            fn primary(&mut self, _: char, _: &mut Tail<'_, I>) -> Result<()> {
+                Ok(())
+            }
+
+            fn infix(&mut self, _: (), _: char, _: ()) -> Result<()> {
+                Ok(())
+            }
+
+            fn prefix(&mut self, _: char, _: ()) -> Result<()> {
+                Ok(())
+            }
+
+            fn postfix(&mut self, _: (), _: char) -> Result<()> {
+                Err(NoError)
+            }
+        }
+
+        // Each input is classified once, in the innermost expression which
+        // reaches it
+        let mut record = Record::default();
+        record.parse("-a*b+c".chars()).unwrap();
+        let (nud, led) = (Denotation::Nud, Denotation::Led);
+        assert_eq!(
+            record.0,
+            [
+                ('-', nud, 0),
+                ('a', nud, 29),
+                ('*', led, 29),
+                ('b', nud, 20),
+                ('+', led, 20),
+                ('c', nud, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn lookahead() {
+        /// Reads `<` as opening type arguments where the input after next
+        /// closes them, so that `f<T>` reads as type arguments but `a<b` as
+        /// a comparison.
+        struct Generics;
+
+        impl<I: Iterator<Item = char> + Clone> PrattParser<I> for Generics {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '<' => Affix::Infix(Precedence(1), Associativity::Neither),
+                    '+' => Affix::Infix(Precedence(2), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn query_with_context(
+                &mut self,
+                context: &mut QueryContext<'_, I>,
+            ) -> Result<Option<Affix>> {
+                let c = *context.input();
+                let led = context.denotation() == Denotation::Led;
+                match c {
+                    '<' if led && context.lookahead(1) == Some('>') => {
+                        Ok(Some(Affix::PostfixWith(Precedence(9))))
+                    }
+                    c => PrattParser::<I>::query(self, &c).map(Some),
+                }
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.into())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                unreachable!()
+            }
+
+            fn postfix_with(
+                &mut self,
+                lhs: String,
+                _: char,
+                tail: &mut Tail<'_, I>,
+            ) -> Result<String, PrattError<char, NoError>> {
+                // The argument and the closing `>` are known to follow
+                let argument = tail.next().unwrap();
+                tail.next();
+                Ok(std::format!("{}<{}>", lhs, argument))
+            }
+        }
+
+        let parse = |input: &str| Generics.parse(input.chars()).unwrap();
+        assert_eq!(parse("f<T>+1"), "(f<T>+1)");
+        assert_eq!(parse("a<b+c"), "(a<(b+c))");
+        assert_eq!(parse("a+f<T><b"), "((a+f<T>)<b)");
+        assert_eq!(parse("a<b"), "(a<b)");
+    }
+
+    #[test]
+    fn optional_postfix_tail() {
+        /// Parses `a?` as `a` or a default of 0, and `a?:b` with a default of `b`.
+        struct Fallback;
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Fallback {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(1), Associativity::Left),
+                    '?' => Affix::PostfixWith(Precedence(2)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.into())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                unreachable!()
+            }
+
+            fn postfix_with(
+                &mut self,
+                lhs: String,
+                _: char,
+                tail: &mut Tail<'_, I>,
+            ) -> Result<String, PrattError<char, NoError>> {
+                let fallback = match tail.next_if(|&c| c == ':') {
+                    Some(_) => {
+                        let rbp = PrattParser::<I>::rbp(self, Precedence(2), Associativity::Left);
+                        self.parse_input(tail, rbp).map_err(|_| NoError)?
+                    }
+                    None => "0".into(),
+                };
+                Ok(std::format!("({}?{})", lhs, fallback))
+            }
+        }
+
+        let parse = |input: &str| Fallback.parse(input.chars()).unwrap();
+        assert_eq!(parse("1?"), "(1?0)");
+        assert_eq!(parse("1?:2"), "(1?2)");
+        assert_eq!(parse("1?+2"), "((1?0)+2)");
+        assert_eq!(parse("1?:2+3"), "((1?2)+3)");
+        assert_eq!(parse("1?:2?"), "((1?2)?0)");
+    }
+
+    #[test]
+    fn juxtaposition() {
+        /// Applies functions to arguments written after them, as in `f x y`.
+        struct Apply;
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Apply {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(1), Associativity::Left),
+                    '-' => Affix::Prefix(Precedence(2)),
+                    '!' => Affix::Postfix(Precedence(4)),
+                    '(' => Affix::Circumfix,
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.into())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{})", op, rhs))
+            }
+
+            fn postfix(&mut self, lhs: String, op: char) -> Result<String> {
+                Ok(std::format!("({}{})", lhs, op))
+            }
+
+            fn circumfix(
+                &mut self,
+                _: char,
+                inner: String,
+                _: char,
+            ) -> Result<String, PrattError<char, NoError>> {
+                Ok(inner)
+            }
+
+            fn closes(&open: &char, &close: &char) -> bool {
+                open == '(' && close == ')'
+            }
+
+            fn juxtaposition(&self) -> Option<Precedence> {
+                Some(Precedence(3))
+            }
+
+            fn juxtapose(
+                &mut self,
+                lhs: String,
+                rhs: String,
+            ) -> Result<String, PrattError<char, NoError>> {
+                Ok(std::format!("({} {})", lhs, rhs))
+            }
+        }
+
+        for (input, expected) in [
+            ("fxy", "((f x) y)"),
+            ("fx+gy", "((f x)+(g y))"),
+            ("f(x+y)z", "((f (x+y)) z)"),
+            ("fx!y", "((f (x!)) y)"),
+            ("f-xy", "(f (-(x y)))"),
+            ("-fx", "(-(f x))"),
+        ] {
+            assert_eq!(Apply.parse(input.chars()).unwrap(), expected);
+            #[cfg(feature = "alloc")]
+            assert_eq!(Apply.parse_iterative(input.chars()).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn member_access() {
+        assert_eq!(show(&parse("1.2.3*4").unwrap()), "(((1.2).3)*4)");
+        assert_eq!(show(&parse("!1.2[3]").unwrap()), "(!(1.2)[3])");
+        assert_eq!(show(&parse("(1+2).3").unwrap()), "((1+2).3)");
+        assert!(matches!(
+            parse("1.(2)"),
+            Err(PrattError::UserError(NoError))
+        ));
+        assert!(matches!(parse("1."), Err(PrattError::UserError(NoError))));
+    }
+
+    #[test]
+    fn recover() {
+        let mut parser = Custom(Recovering::default());
+        let expr = parser.parse(tokens("(1+*2")).unwrap();
+        assert_eq!(show(&expr), "_");
+        assert_eq!(
+            parser.0.errors,
+            [
+                "Expected Nilfix or Prefix, found Infix (3, '*')",
+                "Expected a delimiter closing (0, '(')",
+            ]
+        );
+
+        let mut parser = Custom(Recovering::default());
+        let expr = parser.parse(tokens("1+2*")).unwrap();
+        assert_eq!(show(&expr), "(1+(2*_))");
+        assert_eq!(parser.0.errors, ["Expected an operand after (3, '*')"]);
+    }
+
+    #[test]
+    fn expressions() {
+        let show_all = |input: &str| {
+            ExprParser
+                .expressions(tokens(input), |&(_, c)| c == ';')
+                .map(|expr| expr.map(|expr| show(&expr)).map_err(|e| e.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            show_all("1+2;3*4"),
+            [Ok("(1+2)".into()), Ok("(3*4)".into())]
+        );
+        assert_eq!(show_all("1;2;"), [Ok("1".into()), Ok("2".into())]);
+        assert!(show_all("").is_empty());
+        assert_eq!(
+            show_all("1;;2"),
+            [
+                Ok("1".into()),
+                Err(PrattError::<Token, NoError>::EmptyInput.to_string())
+            ]
+        );
+
+        let mut parser = ExprParser;
+        let mut exprs = parser.expressions(tokens("1+;2"), |&(_, c)| c == ';');
+        assert!(matches!(
+            exprs.next(),
+            Some(Err(PrattError::MissingOperand((1, '+'), 1)))
+        ));
+        assert!(exprs.next().is_none());
+        assert_eq!(exprs.inputs().next(), Some((2, ';')));
+    }
+
+    #[test]
+    fn expressions_missing_terminator() {
+        let mut parser = ExprParser;
+        let mut exprs = parser.expressions(tokens("1;2 3;4"), |&(_, c)| c == ';');
+        assert_eq!(
+            exprs.next().map(|expr| show(&expr.unwrap())),
+            Some("1".into())
+        );
+        assert!(matches!(
+            exprs.next(),
+            Some(Err(PrattError::MissingTerminator(Some(((4, '3'), 4)))))
+        ));
+        assert!(exprs.next().is_none());
+        assert_eq!(exprs.inputs().next(), Some((5, ';')));
+    }
+
+    #[test]
+    fn missing_operand() {
+        assert!(matches!(parse(""), Err(PrattError::EmptyInput)));
+        assert!(matches!(
+            parse("!"),
+            Err(PrattError::MissingOperand((0, '!'), 0))
+        ));
+        assert!(matches!(
+            parse("1+(!)"),
+            Err(PrattError::MissingOperand((3, '!'), 3))
+        ));
+        // An operator in place of the operand is pointed at instead
+        assert!(matches!(
+            parse("!*"),
+            Err(PrattError::UnexpectedInfix((1, '*'), 1))
+        ));
+        assert!(matches!(
+            parse("1*!*2"),
+            Err(PrattError::UnexpectedInfix((3, '*'), 3))
+        ));
+        assert!(matches!(
+            parse("1+()"),
+            Err(PrattError::MissingOperand((2, '('), 2))
+        ));
+        assert!(matches!(
+            parse("1?:2"),
+            Err(PrattError::MissingOperand((1, '?'), 1))
+        ));
+        assert!(matches!(
+            parse("1?2:"),
+            Err(PrattError::MissingOperand((3, ':'), 3))
+        ));
+        assert_eq!(
+            parse("1+!").unwrap_err().to_string(),
+            "Expected an operand after (2, '!')"
+        );
+
+        #[cfg(feature = "alloc")]
+        for input in ["!", "1+(!)", "1+()", "1?:2", "1?2:", "1&2&", "f1h2e", "f1h"] {
+            let expected = parse(input).map(|expr| show(&expr));
+            let actual = ExprParser
+                .parse_iterative(tokens(input))
+                .map(|expr| show(&expr));
+            assert_eq!(std::format!("{:?}", actual), std::format!("{:?}", expected));
+        }
+    }
+
+    #[test]
+    fn error_position() {
+        match parse("1 + * 2") {
+            Err(PrattError::UnexpectedInfix((_, '*'), 4)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(parse("").unwrap_err().position(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_iterative() {
+        let inputs = [
+            "1*2+3^4^5",
+            "1=2",
+            "!1^2^3$",
+            "1*!2^2",
+            "~1$$*2",
+            "1?2?3:4:5",
+            "1?2:3?4:5",
+            "1+2?3:4=5",
+            "(1+2)*3",
+            "((1))",
+            "1[1+2][3]",
+            "!(1?(2):3)$",
+            "",
+            "1+",
+            "1+*2",
+            "(1+2",
+            "1?2",
+            "1=2=3",
+            "1 2",
+            "1+2;3",
+            "~1~~2*3",
+            "!t1",
+            "tt1",
+            "(t!1)",
+            ";",
+            "(1;2)",
+        ];
+        for input in inputs {
+            let expected = parse(input).map(|expr| show(&expr));
+            let actual = ExprParser
+                .parse_iterative(tokens(input))
+                .map(|expr| show(&expr));
+            assert_eq!(
+                std::format!("{:?}", actual),
+                std::format!("{:?}", expected),
+                "{}",
+                input
+            );
+        }
+
+        for input in ["(1+*2", "1+2*", "(1+2"] {
+            let mut recursive = Custom(Recovering::default());
+            let mut iterative = Custom(Recovering::default());
+            let expected = recursive.parse(tokens(input)).unwrap();
+            let actual = iterative.parse_iterative(tokens(input)).unwrap();
+            assert_eq!(show(&actual), show(&expected));
+            assert_eq!(iterative.0.errors, recursive.0.errors);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_iterative_deep() {
+        let depth = 1_000_000;
+        let input = "!".repeat(depth) + "1";
+        let mut expr = ExprParser.parse_iterative(tokens(&input)).unwrap();
+        for _ in 0..depth {
+            expr = match expr {
+                Expr::UnOp(_, rhs) => *rhs,
+                _ => panic!("expected a prefix operator"),
+            };
+        }
+        assert!(matches!(expr, Expr::Int(1)));
+    }
+
+    #[test]
+    fn max_depth() {
+        let accepted = [
+            "1+2+3+4",
+            "!!1",
+            "((1))",
+            "(!1)",
+            "1^2^3",
+            "1?2:3?4:5",
+            "(1?2:3)",
+        ];
+        for input in accepted {
+            let expr = Custom(Limited(2)).parse(tokens(input));
+            assert_eq!(
+                show(&expr.unwrap()),
+                show(&parse(input).unwrap()),
+                "{}",
+                input
+            );
+        }
+        let rejected = [
+            "!!!1",
+            "(((1)))",
+            "!(!1)",
+            "1^2^3^4",
+            "1?2:3?4:5?6:7",
+            "(1?(2):3)",
+        ];
+        for input in rejected {
+            let expr = Custom(Limited(2)).parse(tokens(input));
+            assert!(matches!(expr, Err(PrattError::DepthExceeded)), "{}", input);
+        }
+
+        let input = "!".repeat(1_000_000) + "1";
+        let expr = Custom(Limited(100)).parse(tokens(&input));
+        assert!(matches!(expr, Err(PrattError::DepthExceeded)));
+
+        // Each right-associative operator nests its right operand one deeper
+        let input = "2^".repeat(256) + "2";
+        assert!(Custom(Limited(256)).parse(tokens(&input)).is_ok());
+        let input = "2^".repeat(1_000) + "2";
+        let expr = Custom(Limited(256)).parse(tokens(&input));
+        assert!(matches!(expr, Err(PrattError::DepthExceeded)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn max_depth_iterative() {
+        let inputs = [
+            "!!1",
+            "!!!1",
+            "((1))",
+            "(((1)))",
+            "1^2^3",
+            "1^2^3^4",
+            "1?2:3?4:5?6:7",
+        ];
+        for input in inputs {
+            let expected = Custom(Limited(2)).parse(tokens(input));
+            let actual = Custom(Limited(2)).parse_iterative(tokens(input));
+            assert_eq!(
+                std::format!("{:?}", actual.map(|expr| show(&expr))),
+                std::format!("{:?}", expected.map(|expr| show(&expr))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_all_errors() {
+        let mut parser = Custom(Recovering::default());
+        let (expr, errors) = parser.parse_all_errors(tokens("1*(2?3+*"));
+        assert_eq!(show(&expr.unwrap()), "(1*_)");
+        assert!(matches!(
+            errors[..],
+            [
+                PrattError::UnexpectedInfix((7, '*'), _),
+                PrattError::UnclosedDelimiter((4, '?'), _),
+                PrattError::UnclosedDelimiter((2, '('), _),
+            ]
+        ));
+        assert_eq!(parser.0.errors.len(), 3);
+
+        let (expr, errors) = parser.parse_all_errors(tokens("(1+*)*(2-"));
+        assert_eq!(show(&expr.unwrap()), "((1+_)*_)");
+        assert!(matches!(
+            errors[..],
+            [
+                PrattError::UnexpectedInfix((3, '*'), _),
+                PrattError::MissingOperand((8, '-'), _),
+                PrattError::UnclosedDelimiter((6, '('), _),
+            ]
+        ));
+
+        let (expr, errors) = Custom(Recovering::default()).parse_all_errors(tokens("1+2"));
+        assert_eq!(show(&expr.unwrap()), "(1+2)");
+        assert!(errors.is_empty());
+
+        let (expr, errors) = ExprParser.parse_all_errors(tokens("1+*2"));
+        assert!(expr.is_none());
+        assert!(matches!(
+            errors[..],
+            [PrattError::UnexpectedInfix((_, '*'), 2)]
+        ));
+    }
+
+    #[test]
+    fn parse_peekable() {
+        for (input, expr, next) in [
+            ("1+2 3", "(1+2)", Some((4, '3'))),
+            ("!1) 2", "(!1)", Some((2, ')'))),
+            ("1?2:3^4 5", "(1?2:(3^4))", Some((8, '5'))),
+            ("(1)[2] 3", "1[2]", Some((7, '3'))),
+            ("1*2", "(1*2)", None),
+        ] {
+            let mut inputs = tokens(input).into_iter().peekable();
+            let actual = ExprParser.parse_peekable(&mut inputs).unwrap();
+            assert_eq!(show(&actual), expr);
+            assert_eq!(inputs.peek(), next.as_ref());
+        }
+    }
+
+    #[test]
+    fn parse_counted() {
+        for (input, expr, consumed) in [
+            ("1+2 3 4", "(1+2)", 3),
+            ("(1+2)*3", "((1+2)*3)", 7),
+            ("1?2:3;4", "(1?2:3)", 5),
+            ("1[2+3] 4", "1[(2+3)]", 6),
+        ] {
+            let mut inputs = tokens(input).into_iter().peekable();
+            let (actual, n) = ExprParser.parse_counted(&mut inputs).unwrap();
+            assert_eq!(show(&actual), expr);
+            assert_eq!(n, consumed, "{}", input);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let check = |input: &str| {
+            let expected = parse(input).map(|_| ());
+            let actual = ExprParser.validate(tokens(input));
+            assert_eq!(
+                std::format!("{:?}", actual),
+                std::format!("{:?}", expected),
+                "{}",
+                input
+            );
+        };
+        for input in ["1+2*3", "!1^2$", "(1+2", "1+*2", "1=2=3", "1?2", ""] {
+            check(input);
+        }
+        #[cfg(feature = "alloc")]
+        for input in ["1&2|3", "f1h2e3", "f1h"] {
+            check(input);
+        }
+        assert!(Calculator.validate(tokens("1/0")).is_ok());
+        assert!(matches!(
+            ExprParser.validate(tokens("1+2[3]")),
+            Err(PrattError::UnsupportedAffix((3, '['), 3))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn check_associativity() {
+        /// A grammar where `-` was meant to be left-associative, like `+`.
+        struct Mixed;
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Mixed {
+            type Error = NoError;
+            type Input = char;
+            type Output = ();
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '=' | '<' => Affix::Infix(Precedence(2), Associativity::Neither),
+                    '+' => Affix::Infix(Precedence(3), Associativity::Left),
+                    '-' => Affix::Infix(Precedence(3), Associativity::Right),
+                    '*' => Affix::Infix(Precedence(4), Associativity::Left),
+                    '^' => Affix::Infix(Precedence(4), Associativity::Right),
+                    '!' => Affix::Prefix(Precedence(4)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, _: char, _: &mut Tail<'_, I>) -> Result<()> {
+                Ok(())
+            }
+
+            fn infix(&mut self, _: (), _: char, _: ()) -> Result<()> {
+                Ok(())
+            }
+
+            fn prefix(&mut self, _: char, _: ()) -> Result<()> {
+                Ok(())
+            }
+
+            fn postfix(&mut self, _: (), _: char) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let conflict = |samples: &str| {
+            PrattParser::<core::str::Chars>::check_associativity(&mut Mixed, samples.chars())
+                .unwrap()
+        };
+        assert_eq!(conflict("+-"), Some(('+', '-')));
+        assert_eq!(conflict("=<+*!"), None);
+        assert_eq!(conflict("*+=-^"), Some(('+', '-')));
+        assert_eq!(conflict("^!1*"), Some(('^', '*')));
+
+        let conflict = |samples: &str| {
+            PrattParser::<std::vec::IntoIter<Token>>::check_associativity(
+                &mut ExprParser,
+                tokens(samples),
+            )
+            .unwrap()
+        };
+        assert!(conflict("=<+-&|~*/%?^").is_none());
+    }
+
+    #[test]
+    fn token_index() {
+        // Tokens without spans are numbered by `enumerate`
+        let expr = ExprParser.parse("!1+*2".chars().enumerate());
+        assert!(matches!(
+            expr,
+            Err(PrattError::UnexpectedInfix((3, '*'), 3))
+        ));
+        let error = ExprParser.parse("1+(2".chars().enumerate()).unwrap_err();
+        assert_eq!(error.position(), Some(&2));
+    }
+
+    #[test]
+    fn neighbouring_nilfixes() {
+        for (input, expr, rest) in [
+            ("1 2", "1", &[(2, '2')][..]),
+            ("1 2 3", "1", &[(2, '2'), (4, '3')]),
+            ("1+2 3 4", "(1+2)", &[(4, '3'), (6, '4')]),
+        ] {
+            let mut inputs = tokens(input).into_iter().peekable();
+            let actual = ExprParser.parse_peekable(&mut inputs).unwrap();
+            assert_eq!(show(&actual), expr);
+            assert_eq!(inputs.collect::<Vec<_>>(), rest);
+
+            // `parse` only loses the input it stopped at
+            let mut inputs = tokens(input).into_iter();
+            let actual = ExprParser.parse(&mut inputs).unwrap();
+            assert_eq!(show(&actual), expr);
+            assert_eq!(inputs.collect::<Vec<_>>(), rest[1..]);
+
+            #[cfg(feature = "alloc")]
+            {
+                let mut inputs = tokens(input).into_iter();
+                let actual = ExprParser.parse_iterative(&mut inputs).unwrap();
+                assert_eq!(show(&actual), expr);
+                assert_eq!(inputs.collect::<Vec<_>>(), rest[1..]);
+            }
+        }
+    }
+
+    #[test]
+    fn statements() {
+        let mut inputs = tokens("1+2; 3*4").into_iter().peekable();
+        let mut exprs = Vec::new();
+        loop {
+            let expr = ExprParser.parse_until(&mut inputs, |&(_, c)| c == ';');
+            exprs.push(show(&expr.unwrap()));
+            if inputs.next().is_none() {
+                break;
+            }
+        }
+        assert_eq!(exprs, ["(1+2)", "(3*4)"]);
+    }
+
+    #[test]
+    fn prefix_precedence() {
+        assert_eq!(show(&parse("!2^2").unwrap()), "(!(2^2))");
+        assert_eq!(show(&parse("~2^2").unwrap()), "((~2)^2)");
+        assert_eq!(show(&parse("2^!3^4").unwrap()), "(2^(!(3^4)))");
+        assert_eq!(show(&parse("2^~3^4").unwrap()), "(2^((~3)^4))");
+        // Tighter than `*` but looser than `^`, like unary minus in maths.
+        assert_eq!(show(&parse("2^!2").unwrap()), "(2^(!2))");
+        assert_eq!(show(&parse("!2*3").unwrap()), "((!2)*3)");
+        assert_eq!(show(&parse("1*!2^2").unwrap()), "(1*(!(2^2)))");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn std_error() {
+        use std::error::Error;
+
+        #[derive(Debug)]
+        struct Overflow;
+
+        impl core::fmt::Display for Overflow {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "overflow")
+            }
+        }
+
+        impl Error for Overflow {}
+
+        let error: PrattError<Token, Overflow, usize> = PrattError::UserError(Overflow);
+        let error: Box<dyn Error> = Box::new(error);
+        assert_eq!(error.source().unwrap().to_string(), "overflow");
+
+        let error: Box<dyn Error> = Box::new(parse("1+").unwrap_err());
+        assert!(error.source().is_none());
+
+        fn boxed(input: &str) -> core::result::Result<String, Box<dyn Error>> {
+            Ok(show(&parse(input)?))
+        }
+        assert_eq!(boxed("1+2").unwrap(), "(1+2)");
+        assert_eq!(
+            boxed("1+").unwrap_err().to_string(),
+            "Expected an operand after (1, '+')"
+        );
+    }
+
+    #[test]
+    fn error_operand() {
+        // The factorial `!` of a negative number fails with its operand.
+        struct Factorial;
+
+        #[derive(Debug)]
+        struct Negative(String);
+
+        impl core::fmt::Display for Negative {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "{} is negative", self.0)
+            }
+        }
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Factorial {
+            type Error = Negative;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> core::result::Result<Affix, Negative> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(2), Associativity::Left),
+                    '~' => Affix::Prefix(Precedence(4)),
+                    '!' => Affix::Postfix(Precedence(3)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(
+                &mut self,
+                c: char,
+                _: &mut Tail<'_, I>,
+            ) -> core::result::Result<String, Negative> {
+                Ok(c.to_string())
+            }
+
+            fn infix(
+                &mut self,
+                lhs: String,
+                _: char,
+                rhs: String,
+            ) -> core::result::Result<String, Negative> {
+                Ok(std::format!("{}+{}", lhs, rhs))
+            }
+
+            fn prefix(&mut self, _: char, rhs: String) -> core::result::Result<String, Negative> {
+                Ok(std::format!("-{}", rhs))
+            }
+
+            fn postfix(&mut self, lhs: String, _: char) -> core::result::Result<String, Negative> {
+                match lhs.starts_with('-') {
+                    true => Err(Negative(lhs)),
+                    false => Ok(std::format!("{}!", lhs)),
+                }
+            }
+        }
+
+        assert_eq!(Factorial.parse("3!+~2".chars()).unwrap(), "3!+-2");
+        match Factorial.parse("1+~3!".chars()) {
+            Err(PrattError::UserError(Negative(lhs))) => assert_eq!(lhs, "-3"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn operators_macro() {
+        fn affix(c: char, led: bool) -> core::result::Result<Affix, char> {
+            Ok(crate::operators!(c, led;
+                '+' | '*' => Infix(3, Left),
+                '-' => Prefix(6),
+                led '-' => Infix(3, Left),
+                '!' => Postfix(5),
+                '(' => Circumfix,
+                '#' => { return Err(c) },
+                _ => Nilfix,
+            ))
+        }
+
+        assert!(matches!(
+            affix('*', false),
+            Ok(Affix::Infix(Precedence(3), Associativity::Left))
+        ));
+        assert!(matches!(
+            affix('-', false),
+            Ok(Affix::Prefix(Precedence(6)))
+        ));
+        assert!(matches!(
+            affix('-', true),
+            Ok(Affix::Infix(Precedence(3), Associativity::Left))
+        ));
+        assert!(matches!(
+            affix('!', true),
+            Ok(Affix::Postfix(Precedence(5)))
+        ));
+        assert!(matches!(affix('(', false), Ok(Affix::Circumfix)));
+        assert!(matches!(affix('1', true), Ok(Affix::Nilfix)));
+        assert!(matches!(affix('#', false), Err('#')));
+
+        #[cfg(feature = "alloc")]
+        assert!(matches!(
+            crate::operators!('f'; 'f' => Mixfix(1, 2), _ => Nilfix),
+            Affix::Mixfix(Precedence(1), 2)
+        ));
+    }
+
+    #[test]
+    fn rpn() {
+        /// Writes the expression in reverse Polish notation.
+        #[derive(Default)]
+        struct Rpn(String);
+
+        impl<I: Iterator<Item = Token>> Hooks<I> for Rpn {
+            fn primary(&mut self, input: Token, tail: &mut Tail<'_, I>) -> Result<Expr> {
+                self.0.push(input.1);
+                ExprParser.primary(input, tail)
+            }
+
+            fn infix(&mut self, lhs: Expr, op: Token, rhs: Expr) -> Result<Expr> {
+                self.0.push(op.1);
+                PrattParser::<I>::infix(&mut ExprParser, lhs, op, rhs)
+            }
+
+            fn prefix(&mut self, op: Token, rhs: Expr) -> Result<Expr> {
+                self.0.push(op.1);
+                PrattParser::<I>::prefix(&mut ExprParser, op, rhs)
+            }
+
+            fn postfix(&mut self, lhs: Expr, op: Token) -> Result<Expr> {
+                self.0.push(op.1);
+                PrattParser::<I>::postfix(&mut ExprParser, lhs, op)
+            }
+        }
+
+        let rpn = |input: &str| {
+            let mut rpn = Custom(Rpn::default());
+            rpn.parse(tokens(input)).unwrap();
+            rpn.0 .0
+        };
+        assert_eq!(rpn("1+2*3"), "123*+");
+        assert_eq!(rpn("1*2+3"), "12*3+");
+        assert_eq!(rpn("1-2-3"), "12-3-");
+        assert_eq!(rpn("1^2^3"), "123^^");
+        assert_eq!(rpn("(1+2)*3"), "12+3*");
+        assert_eq!(rpn("!1^2$*3"), "12^!$3*");
+        assert_eq!(rpn("1~2"), "12~");
+    }
+
+    #[test]
+    fn tagged_inputs() {
+        /// A character which has been looked at once, with what it means.
+        #[derive(Debug, Clone, Copy)]
+        enum Op {
+            Num(i64),
+            Binary(u32, fn(i64, i64) -> i64),
+            Unary(u32, fn(i64) -> i64),
+        }
+
+        fn tag(c: char) -> Op {
+            match c {
+                '+' => Op::Binary(1, |a, b| a + b),
+                '*' => Op::Binary(2, |a, b| a * b),
+                '-' => Op::Unary(3, |a| -a),
+                _ => Op::Num(c.to_digit(10).unwrap() as i64),
+            }
+        }
+
+        struct Tagged;
+
+        impl<I: Iterator<Item = Op>> PrattParser<I> for Tagged {
+            type Error = NoError;
+            type Input = Op;
+            type Output = i64;
+            type Position = ();
+
+            fn query(&mut self, op: &Op) -> Result<Affix> {
+                Ok(match *op {
+                    Op::Num(_) => Affix::Nilfix,
+                    Op::Binary(precedence, _) => {
+                        Affix::Infix(Precedence(precedence), Associativity::Left)
+                    }
+                    Op::Unary(precedence, _) => Affix::Prefix(Precedence(precedence)),
+                })
+            }
+
+            fn primary(&mut self, op: Op, _: &mut Tail<'_, I>) -> Result<i64> {
+                match op {
+                    Op::Num(n) => Ok(n),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn infix(&mut self, lhs: i64, op: Op, rhs: i64) -> Result<i64> {
+                match op {
+                    Op::Binary(_, f) => Ok(f(lhs, rhs)),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn prefix(&mut self, op: Op, rhs: i64) -> Result<i64> {
+                match op {
+                    Op::Unary(_, f) => Ok(f(rhs)),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn postfix(&mut self, _: i64, _: Op) -> Result<i64> {
+                unreachable!()
+            }
+        }
+
+        let evaluate = |input: &str| Tagged.parse(input.chars().map(tag)).unwrap();
+        assert_eq!(evaluate("1+2*3"), 7);
+        assert_eq!(evaluate("-1*2+-3"), -5);
+    }
+
+    #[test]
+    fn on_empty() {
+        /// An empty expression stands for 0, or fails with a `UserError`.
+        struct Empty(bool);
+
+        impl<I: Iterator<Item = Token>> Hooks<I> for Empty {
+            fn on_empty(&mut self) -> Result<Option<Expr>> {
+                match self.0 {
+                    true => Ok(Some(Expr::Int(0))),
+                    false => Err(NoError),
+                }
+            }
+        }
+
+        let expr = Custom(Empty(true)).parse(tokens(""));
+        assert_eq!(show(&expr.unwrap()), "0");
+        let mut inputs = tokens(";1").into_iter().peekable();
+        let expr = Custom(Empty(true)).parse_until(&mut inputs, |&(_, c)| c == ';');
+        assert_eq!(show(&expr.unwrap()), "0");
+        let expr = Custom(Empty(false)).parse(tokens(""));
+        assert!(matches!(expr, Err(PrattError::UserError(NoError))));
+        #[cfg(feature = "alloc")]
+        {
+            let expr = Custom(Empty(true)).parse_iterative(tokens(""));
+            assert_eq!(show(&expr.unwrap()), "0");
+            let (expr, errors) = Custom(Empty(true)).parse_all_errors(tokens(""));
+            assert_eq!(show(&expr.unwrap()), "0");
+            assert!(errors.is_empty());
+            let (expr, errors) = Custom(Empty(false)).parse_all_errors(tokens(""));
+            assert!(expr.is_none());
+            assert!(matches!(errors[..], [PrattError::UserError(NoError)]));
+        }
+
+        // A missing operand is not an empty expression
+        let expr = Custom(Empty(true)).parse(tokens("1+"));
+        assert!(matches!(expr, Err(PrattError::MissingOperand((1, '+'), _))));
+    }
+
+    #[test]
+    fn postfix_or_infix() {
+        let inputs = [
+            ("1%2", "(1%2)"),
+            ("1%", "(%1)"),
+            ("1%+2", "((%1)+2)"),
+            ("1%!2", "(1%(!2))"),
+            ("1%(2)", "(1%2)"),
+            ("(1%)*2", "((%1)*2)"),
+            ("1%%2", "((%1)%2)"),
+            ("1+2%*3", "(1+((%2)*3))"),
+        ];
+        for (input, expr) in inputs {
+            assert_eq!(show(&parse(input).unwrap()), expr, "{}", input);
+            #[cfg(feature = "alloc")]
+            {
+                let actual = ExprParser.parse_iterative(tokens(input));
+                assert_eq!(show(&actual.unwrap()), expr, "{}", input);
+            }
+        }
+        assert!(matches!(
+            parse("%1"),
+            Err(PrattError::UnexpectedInfix((0, '%'), 0))
+        ));
+    }
+
+    #[test]
+    fn evaluate() {
+        let evaluate = |input: &str| Calculator.evaluate(tokens(input)).unwrap();
+        assert_eq!(evaluate("1*2+3^2^2"), 83);
+        assert_eq!(evaluate("9-4-3"), 2);
+        assert_eq!(evaluate("~2^2"), 4);
+        assert_eq!(evaluate("!2^2"), 0);
+        assert_eq!(evaluate("(1+2)$*2"), 18);
+        assert_eq!(evaluate("1=2?3:4+5"), 9);
+    }
+
+    /// Counts the allocations made by each thread, so that tests running in
+    /// parallel do not disturb each other.
+    struct CountingAllocator;
+
+    std::thread_local! {
+        static ALLOCATIONS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+    }
+
+    unsafe impl core::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: core::alloc::Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: core::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn evaluate_does_not_allocate() {
+        for input in ["1*2+3^2^2", "~2^2", "(1+2)$*2", "1=2?3:4+5", "((((1))))"] {
+            let tokens = tokens(input);
+            let before = ALLOCATIONS.with(|count| count.get());
+            let value = Calculator.evaluate(tokens.iter().copied());
+            let after = ALLOCATIONS.with(|count| count.get());
+            assert!(value.is_ok());
+            assert_eq!(after, before, "{}", input);
+        }
+
+        // Building a tree does allocate, so the count is not vacuous
+        let tokens = tokens("1+2");
+        let before = ALLOCATIONS.with(|count| count.get());
+        let expr = ExprParser.parse(tokens.iter().copied());
+        let after = ALLOCATIONS.with(|count| count.get());
+        assert!(expr.is_ok());
+        assert!(after > before);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_iterative_right_associative() {
+        let depth = 1_000_000;
+        let input = "1^".repeat(depth) + "2";
+        let mut expr = ExprParser.parse_iterative(tokens(&input)).unwrap();
+        for _ in 0..depth {
+            expr = match expr {
+                Expr::BinOp(lhs, '^', rhs) if matches!(*lhs, Expr::Int(1)) => *rhs,
+                _ => panic!("expected a right-associative chain"),
+            };
+        }
+        assert!(matches!(expr, Expr::Int(2)));
+    }
+
+    #[test]
+    fn classify() {
+        let mut inputs = tokens("1+!2;3").into_iter().peekable();
+        let expr = ExprParser.parse_peekable(&mut inputs);
+        assert_eq!(show(&expr.unwrap()), "(1+(!2))");
+        assert_eq!(inputs.next(), Some((4, ';')));
+
+        let mut inputs = tokens(";").into_iter().peekable();
+        let expr = ExprParser.parse_peekable(&mut inputs);
+        assert!(matches!(expr, Err(PrattError::EmptyInput)));
+        assert_eq!(inputs.next(), Some((0, ';')));
+
+        assert!(matches!(
+            parse("(1;2)"),
+            Err(PrattError::UnclosedDelimiter((0, '('), 0))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn infix_chain() {
+        assert_eq!(show(&parse("1&2|3&4").unwrap()), "(1&2|3&4)");
+        assert_eq!(show(&parse("1&2*3|4").unwrap()), "(1&(2*3)|4)");
+        assert_eq!(show(&parse("1&2+3&4").unwrap()), "(((1&2)+3)&4)");
+        assert_eq!(show(&parse("1=2&3|4").unwrap()), "(1=(2&3|4))");
+        assert_eq!(show(&parse("!1&(2&3)").unwrap()), "((!1)&(2&3))");
+        assert_eq!(show(&parse("1?2&3:4&5").unwrap()), "(1?(2&3):(4&5))");
+        assert_eq!(show(&parse("1&2^3^4&5").unwrap()), "(1&(2^(3^4))&5)");
+        assert_eq!(show(&parse("!1&!2&3").unwrap()), "((!1)&(!2)&3)");
+        assert_eq!(show(&parse("1&r2&3").unwrap()), "(1&(r(2&3)))");
+
+        let evaluate = |input: &str| Calculator.evaluate(tokens(input)).unwrap();
+        assert_eq!(evaluate("7&3&6"), 2);
+
+        for input in [
+            "1&2|3&4",
+            "1&2+3&4",
+            "(1&2)&3",
+            "1?2&3:4&5",
+            "1&2^3^4&5",
+            "1&r2&3",
+            "1&",
+            "1&2&(3",
+        ] {
+            let expected = parse(input).map(|expr| show(&expr));
+            let actual = ExprParser
+                .parse_iterative(tokens(input))
+                .map(|expr| show(&expr));
+            assert_eq!(
+                std::format!("{:?}", actual),
+                std::format!("{:?}", expected),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn into_iterator() {
+        let expr = ExprParser.parse([(0, '1'), (1, '+'), (2, '2')]).unwrap();
+        assert_eq!(show(&expr), "(1+2)");
+        let expr = ExprParser.parse_fresh(tokens("1*2")).unwrap();
+        assert_eq!(show(&expr), "(1*2)");
+
+        let mut inputs = tokens("1+2;3").into_iter().peekable();
+        assert_eq!(show(&ExprParser.parse(&mut inputs).unwrap()), "(1+2)");
+        assert_eq!(inputs.next(), Some((4, '3')));
+    }
+
+    #[test]
+    fn query_led() {
+        assert_eq!(show(&parse("1~2").unwrap()), "(1~2)");
+        assert_eq!(show(&parse("~1~~2*3").unwrap()), "((~1)~((~2)*3))");
+        assert_eq!(show(&parse("1~2~3").unwrap()), "((1~2)~3)");
+        assert_eq!(show(&parse("1~2;3").unwrap()), "(1~2)");
+    }
+
+    #[test]
+    fn query_once() {
+        /// Counts how often inputs are classified.
+        struct Counting(usize);
+
+        impl<I: Iterator<Item = Token>> Hooks<I> for Counting {
+            fn query(&mut self, input: &Token) -> Result<Affix> {
+                self.0 += 1;
+                match input {
+                    (_, '@') => Ok(Affix::Nilfix),
+                    _ => PrattParser::<I>::query(&mut ExprParser, input),
+                }
+            }
+
+            fn query_led(&mut self, input: &Token) -> Result<Option<Affix>> {
+                self.0 += 1;
+                PrattParser::<I>::query_led(&mut ExprParser, input)
+            }
+
+            fn primary(&mut self, input: Token, tail: &mut Tail<'_, I>) -> Result<Expr> {
+                if input.1 != '@' {
+                    return ExprParser.primary(input, tail);
+                }
+                // `@` takes the operand after it like `!`, from a tail of its
+                // own which ends in front of the operator after the operand
+                let mut stop = |_: &Token| false;
+                let mut inner = Custom(Counting(0));
+                let rbp = Precedence(6).normalize();
+                let rhs = inner.parse_input(&mut tail.until(&mut stop), rbp);
+                self.0 += inner.0 .0;
+                match rhs {
+                    Ok(rhs) => Ok(Expr::UnOp('@', Box::new(rhs))),
+                    Err(_) => Err(NoError),
+                }
+            }
+        }
+
+        // Inputs which are looked at before they are parsed, after `t` and
+        // `%`, are not queried again either
+        let inputs = [
+            "1*2^3^4+5-6",
+            "!1*2=3",
+            "1-~2$*3",
+            "1^2^3*4*5+6+7",
+            "t1%2",
+            "1%!t2",
+        ];
+        for input in inputs {
+            let mut counting = Custom(Counting(0));
+            let expr = counting.parse(tokens(input)).unwrap();
+            assert_eq!(show(&expr), show(&parse(input).unwrap()));
+            assert_eq!(counting.0 .0, input.len(), "{}", input);
+            #[cfg(feature = "alloc")]
+            {
+                let mut counting = Custom(Counting(0));
+                let expr = counting.parse_iterative(tokens(input)).unwrap();
+                assert_eq!(show(&expr), show(&parse(input).unwrap()));
+                assert_eq!(counting.0 .0, input.len(), "{}", input);
+            }
+        }
+
+        // The operator which ends the operand of `@` is classified inside
+        // the tail of `@`, which hands the classification back
+        let mut counting = Custom(Counting(0));
+        let expr = counting.parse(tokens("@1+2")).unwrap();
+        assert_eq!(show(&expr), "((@1)+2)");
+        assert_eq!(counting.0 .0, 4);
+        #[cfg(feature = "alloc")]
+        {
+            let mut counting = Custom(Counting(0));
+            let expr = counting.parse_iterative(tokens("@1+2")).unwrap();
+            assert_eq!(show(&expr), "((@1)+2)");
+            assert_eq!(counting.0 .0, 4);
+        }
+
+        // Separators are not queried, and each item only once
+        #[cfg(feature = "alloc")]
+        {
+            let mut counting = Custom(Counting(0));
+            let mut inputs = tokens("1+2,3*t4,5").into_iter().peekable();
+            let exprs = counting.parse_all(&mut inputs, |&(_, c)| c == ',', false);
+            assert_eq!(exprs.unwrap().len(), 3);
+            assert_eq!(counting.0 .0, 8);
+        }
+    }
+
+    #[test]
+    fn alternating_associativity() {
+        /// Answers `Left` and `Right` in turn for `+`, so that a `+` which
+        /// is queried twice may change its associativity halfway.
+        struct Alternating(bool);
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Alternating {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => {
+                        self.0 = !self.0;
+                        match self.0 {
+                            true => Affix::Infix(Precedence(3), Associativity::Left),
+                            false => Affix::Infix(Precedence(3), Associativity::Right),
+                        }
+                    }
+                    '(' => Affix::Circumfix,
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.to_string())
+            }
+
+            fn infix(&mut self, lhs: String, _: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}+{})", lhs, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn circumfix(
+                &mut self,
+                _: char,
+                inner: String,
+                _: char,
+            ) -> Result<String, PrattError<char, NoError>> {
+                Ok(inner)
+            }
+
+            fn closes(open: &char, close: &char) -> bool {
+                (*open, *close) == ('(', ')')
+            }
+        }
+
+        let inputs = [
+            ("1+2+3+4+5", "((1+2)+((3+4)+5))"),
+            ("(1+2+3)+4+5", "((((1+2)+3)+4)+5)"),
+            ("1+(2+3+4)+5", "((1+(2+(3+4)))+5)"),
+        ];
+        for (input, expr) in inputs {
+            let actual = Alternating(false).parse(input.chars());
+            assert_eq!(actual.unwrap(), expr, "{}", input);
+            #[cfg(feature = "alloc")]
+            {
+                let actual = Alternating(false).parse_iterative(input.chars());
+                assert_eq!(actual.unwrap(), expr, "{}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn multi_input_operator() {
+        assert_eq!(show(&parse("1+2ni3*4").unwrap()), "((1+2)n(3*4))");
+        assert_eq!(show(&parse("1ni2ni3").unwrap()), "((1n2)n3)");
+        assert!(matches!(
+            parse("1n2"),
+            Err(PrattError::UnexpectedPostfix((1, 'n'), 1))
+        ));
+        assert!(matches!(parse("1ni"), Err(PrattError::EmptyInput)));
+        assert!(matches!(
+            parse("1ni2+"),
+            Err(PrattError::MissingOperand((4, '+'), 4))
+        ));
+    }
+
+    #[test]
+    fn lookahead_operator() {
+        /// Reads `n` as `not`, or as the first input of `not in` where `i`
+        /// follows it.
+        struct Membership;
+
+        impl<I: Iterator<Item = char> + Clone> PrattParser<I> for Membership {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    'n' => Affix::Prefix(Precedence(3)),
+                    '+' => Affix::Infix(Precedence(4), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn query_with_context(
+                &mut self,
+                context: &mut QueryContext<'_, I>,
+            ) -> Result<Option<Affix>> {
+                let (c, led) = (*context.input(), context.denotation() == Denotation::Led);
+                match c {
+                    'n' if led && context.lookahead(0) == Some('i') => {
+                        Ok(Some(Affix::PostfixWith(Precedence(2))))
+                    }
+                    c => PrattParser::<I>::query(self, &c).map(Some),
+                }
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.into())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, rhs: String) -> Result<String> {
+                Ok(std::format!("(not {})", rhs))
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn postfix_with(
+                &mut self,
+                lhs: String,
+                _: char,
+                tail: &mut Tail<'_, I>,
+            ) -> Result<String, PrattError<char, NoError>> {
+                tail.next();
+                let rbp = PrattParser::<I>::rbp(self, Precedence(2), Associativity::Left);
+                let rhs = self.parse_input(tail, rbp)?;
+                Ok(std::format!("({} not in {})", lhs, rhs))
+            }
+        }
+
+        let parse = |input: &str| Membership.parse(input.chars());
+        assert_eq!(parse("anib").unwrap(), "(a not in b)");
+        assert_eq!(parse("nanib+c").unwrap(), "((not a) not in (b+c))");
+        assert_eq!(parse("anina").unwrap(), "(a not in (not a))");
+        assert_eq!(parse("ni").unwrap(), "(not i)");
+        assert!(matches!(parse("ani"), Err(PrattError::EmptyInput)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn compound_operator() {
+        /// Reads `>` as a comparison, `>>` as a shift and `>>=` as a shifting
+        /// assignment, from single-character inputs.
+        struct Shift;
+
+        impl<I: Iterator<Item = char> + Clone> PrattParser<I> for Shift {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '>' => Affix::Infix(Precedence(2), Associativity::Neither),
+                    '+' => Affix::Infix(Precedence(3), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn query_with_context(
+                &mut self,
+                context: &mut QueryContext<'_, I>,
+            ) -> Result<Option<Affix>> {
+                let c = *context.input();
+                if context.denotation() == Denotation::Nud || context.lookahead(0) != Some('>') {
+                    return PrattParser::<I>::query(self, &c).map(Some);
+                }
+                match context.lookahead(1) {
+                    Some('=') => Ok(Some(Affix::PostfixWith(Precedence(1)))),
+                    _ => Ok(Some(Affix::PostfixWith(Precedence(4)))),
+                }
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.into())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn postfix_with(
+                &mut self,
+                lhs: String,
+                _: char,
+                tail: &mut Tail<'_, I>,
+            ) -> Result<String, PrattError<char, NoError>> {
+                // The rest of the operator is known to follow
+                tail.next();
+                let (op, rbp) = match tail.next_if(|&c| c == '=') {
+                    Some(_) => (
+                        ">>=",
+                        PrattParser::<I>::rbp(self, Precedence(1), Associativity::Right),
+                    ),
+                    None => (
+                        ">>",
+                        PrattParser::<I>::rbp(self, Precedence(4), Associativity::Left),
+                    ),
+                };
+                let rhs = self.parse_input(tail, rbp)?;
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+        }
+
+        let parse = |input: &str| Shift.parse(input.chars()).unwrap();
+        assert_eq!(parse("a>b"), "(a>b)");
+        assert_eq!(parse("a>>b+c"), "((a>>b)+c)");
+        assert_eq!(parse("a+b>>c>d"), "((a+(b>>c))>d)");
+        assert_eq!(parse("a>>=b>c"), "(a>>=(b>c))");
+        assert_eq!(parse("a>>=b>>=c>>d"), "(a>>=(b>>=(c>>d)))");
+        assert!(matches!(
+            Shift.parse("a>b>c".chars()),
+            Err(PrattError::NonAssociative('>', ()))
+        ));
+
+        let inputs = "a>>=b+c>>d";
+        assert_eq!(
+            Shift.parse_iterative(inputs.chars()).unwrap(),
+            parse(inputs)
+        );
+        assert_eq!(
+            Shift.parse_all_errors(inputs.chars()).0.unwrap(),
+            parse(inputs)
+        );
+        let error = Shift.validate(inputs.chars()).unwrap_err();
+        assert!(matches!(error, PrattError::UnsupportedAffix('>', ())));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn chained_comparison() {
+        struct Compare;
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Compare {
+            type Error = NoError;
+            type Input = char;
+            type Output = i64;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '<' | '≤' => Affix::InfixChain(Precedence(2)),
+                    '+' => Affix::Infix(Precedence(3), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<i64> {
+                Ok(c.to_digit(10).unwrap() as i64)
+            }
+
+            fn infix(&mut self, lhs: i64, _: char, rhs: i64) -> Result<i64> {
+                Ok(lhs + rhs)
+            }
+
+            fn infix_chain(&mut self, head: i64, rest: Vec<(char, i64)>) -> Result<i64> {
+                let mut lhs = head;
+                let mut holds = true;
+                for (op, rhs) in rest {
+                    holds &= if op == '<' { lhs < rhs } else { lhs <= rhs };
+                    lhs = rhs;
+                }
+                Ok(holds as i64)
+            }
+
+            fn prefix(&mut self, _: char, _: i64) -> Result<i64> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: i64, _: char) -> Result<i64> {
+                unreachable!()
+            }
+        }
+
+        let compare = |input: &str| Compare.parse(input.chars()).unwrap();
+        assert_eq!(compare("1<2≤2"), 1);
+        assert_eq!(compare("1<2<2"), 0);
+        assert_eq!(compare("3<1+3≤4"), 1);
+        assert_eq!(compare("2<1<3"), 0);
+    }
+
+    #[test]
+    fn fallible_inputs() {
+        /// An input which could not be lexed, at the given offset.
+        #[derive(Debug, PartialEq)]
+        struct LexError(usize);
+
+        impl core::fmt::Display for LexError {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "unrecognized input at {}", self.0)
+            }
+        }
+
+        struct Lexed;
+
+        impl<I> PrattParser<I> for Lexed
+        where
+            I: Iterator<Item = core::result::Result<char, usize>>,
+        {
+            type Error = LexError;
+            type Input = core::result::Result<char, usize>;
+            type Output = Expr;
+            type Position = ();
+
+            fn query(&mut self, input: &Self::Input) -> Result<Affix, LexError> {
+                match *input {
+                    Ok('+') => Ok(Affix::Infix(Precedence(3), Associativity::Left)),
+                    Ok(_) => Ok(Affix::Nilfix),
+                    Err(offset) => Err(LexError(offset)),
+                }
+            }
+
+            fn primary(
+                &mut self,
+                input: Self::Input,
+                _: &mut Tail<'_, I>,
+            ) -> Result<Expr, LexError> {
+                Ok(Expr::Int(input.unwrap().to_digit(10).unwrap()))
+            }
+
+            fn infix(&mut self, lhs: Expr, _: Self::Input, rhs: Expr) -> Result<Expr, LexError> {
+                Ok(Expr::BinOp(Box::new(lhs), '+', Box::new(rhs)))
+            }
+
+            fn prefix(&mut self, _: Self::Input, _: Expr) -> Result<Expr, LexError> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: Expr, _: Self::Input) -> Result<Expr, LexError> {
+                unreachable!()
+            }
+        }
+
+        // Lexes lazily, and panics if more than `len` inputs are lexed
+        fn lex(
+            input: &str,
+            len: usize,
+        ) -> impl Iterator<Item = core::result::Result<char, usize>> + '_ {
+            input
+                .char_indices()
+                .map(|(i, c)| (c.is_ascii_digit() || c == '+').then_some(c).ok_or(i))
+                .enumerate()
+                .map(move |(n, input)| {
+                    assert!(n < len, "lexed past the error");
+                    input
+                })
+        }
+
+        let expr = Lexed.parse(lex("1+2+3", 5)).unwrap();
+        assert_eq!(show(&expr), "((1+2)+3)");
+        assert!(matches!(
+            Lexed.parse(lex("1+x+3", 3)),
+            Err(PrattError::UserError(LexError(2)))
+        ));
+        assert!(matches!(
+            Lexed.parse(lex("1+2x", 4)),
+            Err(PrattError::UserError(LexError(3)))
+        ));
+        #[cfg(feature = "alloc")]
+        assert!(matches!(
+            Lexed.parse_iterative(lex("1+x+3", 3)),
+            Err(PrattError::UserError(LexError(2)))
+        ));
+
+        // A stream without end is parsed one expression at a time, and no
+        // further than the first error
+        let endless = "1;2+3;x;".chars().chain(core::iter::repeat('4'));
+        let inputs = endless.enumerate().map(|(i, c)| {
+            (c.is_ascii_digit() || "+;".contains(c))
+                .then_some(c)
+                .ok_or(i)
+        });
+        let mut parser = Lexed;
+        let mut exprs = parser.expressions(inputs, |input| *input == Ok(';'));
+        assert_eq!(show(&exprs.next().unwrap().unwrap()), "1");
+        assert_eq!(show(&exprs.next().unwrap().unwrap()), "(2+3)");
+        assert!(matches!(
+            exprs.next(),
+            Some(Err(PrattError::UserError(LexError(6))))
+        ));
+        assert!(exprs.next().is_none());
+    }
+
+    #[test]
+    fn joined_operator() {
+        fn join(input: &str) -> impl Iterator<Item = Token> {
+            let mut tokens = tokens(input).into_iter().peekable();
+            core::iter::from_fn(move || {
+                let (i, c) = tokens.next()?;
+                match c {
+                    '*' if tokens.next_if(|(_, c)| *c == '*').is_some() => Some((i, '^')),
+                    _ => Some((i, c)),
+                }
+            })
+        }
+        let show_joined = |input| show(&ExprParser.parse(join(input)).unwrap());
+        assert_eq!(show_joined("2**3*2"), "((2^3)*2)");
+        assert_eq!(show_joined("2*3**2**2"), "(2*(3^(2^2)))");
+    }
+
+    #[test]
+    fn parse_fresh() {
+        /// Interns primaries, and outputs the sum of their symbols.
+        struct Interner(Vec<char>);
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Interner {
+            type Error = NoError;
+            type Input = char;
+            type Output = usize;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(3), Associativity::Left),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<usize> {
+                match self.0.iter().position(|interned| *interned == c) {
+                    Some(symbol) => Ok(symbol),
+                    None => {
+                        self.0.push(c);
+                        Ok(self.0.len() - 1)
+                    }
+                }
+            }
+
+            fn infix(&mut self, lhs: usize, _: char, rhs: usize) -> Result<usize> {
+                Ok(lhs + rhs)
+            }
+
+            fn prefix(&mut self, _: char, _: usize) -> Result<usize> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: usize, _: char) -> Result<usize> {
+                unreachable!()
+            }
+
+            fn reset(&mut self) {
+                self.0.clear();
+            }
+        }
+
+        let mut interner = Interner(Vec::new());
+        assert_eq!(interner.parse("a+b+a".chars()).unwrap(), 1);
+        assert_eq!(interner.parse("c".chars()).unwrap(), 2);
+        assert_eq!(interner.parse_fresh("c".chars()).unwrap(), 0);
+        assert_eq!(interner.0, ['c']);
+    }
+
+    #[test]
+    fn loose_prefix() {
+        assert_eq!(show(&parse("r1+2*3").unwrap()), "(r(1+(2*3)))");
+        assert_eq!(show(&parse("r1=2").unwrap()), "(r(1=2))");
+        assert_eq!(show(&parse("r1?2:r3").unwrap()), "(r(1?2:(r3)))");
+        assert_eq!(show(&parse("1+r2+3").unwrap()), "(1+(r(2+3)))");
+        assert_eq!(show(&parse("!r1+2").unwrap()), "(!(r(1+2)))");
+    }
+
+    #[test]
+    fn prefix_primary() {
+        assert_eq!(show(&parse("t1+2").unwrap()), "((t1)+2)");
+        assert_eq!(show(&parse("t(!1)").unwrap()), "(t(!1))");
+        assert_eq!(show(&parse("!t1").unwrap()), "(!(t1))");
+        assert_eq!(show(&parse("!!t1").unwrap()), "(!(!(t1)))");
+        assert!(matches!(
+            parse("tt1"),
+            Err(PrattError::UnexpectedPrefix((1, 't'), 1))
+        ));
+        assert!(matches!(
+            parse("1+t!1"),
+            Err(PrattError::UnexpectedPrefix((3, '!'), 3))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn mixfix() {
+        assert_eq!(show(&parse("f1h2e3").unwrap()), "(f1h2e3)");
+        assert_eq!(
+            show(&parse("f1=2h2+3e3^4").unwrap()),
+            "(f(1=2)h(2+3)e(3^4))"
+        );
+        assert_eq!(show(&parse("1+f1h2e3+4").unwrap()), "(1+(f1h2e(3+4)))");
+        assert_eq!(show(&parse("ff1h2e3h4e5").unwrap()), "(f(f1h2e3)h4e5)");
+        assert_eq!(show(&parse("(f1h2e3)*4").unwrap()), "((f1h2e3)*4)");
+        assert!(matches!(
+            parse("f1h2"),
+            Err(PrattError::UnclosedDelimiter((2, 'h'), 2))
+        ));
+        assert!(matches!(
+            parse("f1e2"),
+            Err(PrattError::UnclosedDelimiter((0, 'f'), 0))
+        ));
+
+        for input in [
+            "f1h2e3+4",
+            "ff1h2e3h4e5",
+            "(f1h2e3)*4",
+            "f1h2",
+            "f1e2",
+            "f(1h2e3",
+        ] {
+            let expected = parse(input).map(|expr| show(&expr));
+            let actual = ExprParser
+                .parse_iterative(tokens(input))
+                .map(|expr| show(&expr));
+            assert_eq!(
+                std::format!("{:?}", actual),
+                std::format!("{:?}", expected),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn builder() {
+        use crate::builder::PrattBuilder;
+
+        let mut parser = PrattBuilder::new(|c: &char| *c)
+            .infix('+', Precedence(3), Associativity::Left, |lhs, rhs| {
+                lhs + rhs
+            })
+            .infix('-', Precedence(3), Associativity::Left, |lhs, rhs| {
+                lhs - rhs
+            })
+            .infix('*', Precedence(4), Associativity::Left, |lhs, rhs| {
+                lhs * rhs
+            })
+            .infix('^', Precedence(7), Associativity::Right, |lhs: i64, rhs| {
+                lhs.pow(rhs as u32)
+            })
+            .prefix('~', Precedence(6), |rhs: i64| -rhs)
+            .postfix('$', Precedence(5), |lhs: i64| lhs * lhs)
+            .primary(|c| c.to_digit(10).unwrap() as i64)
+            .build();
+        let mut evaluate = |input: &str| parser.parse(input.chars()).unwrap();
+        assert_eq!(evaluate("1*2+3^2^2"), 83);
+        assert_eq!(evaluate("9-4-3"), 2);
+        assert_eq!(evaluate("~2^2"), -4);
+        assert_eq!(evaluate("2$*3"), 12);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn operator_table() {
+        use crate::builder::OperatorTable;
+
+        let table = OperatorTable::new()
+            .infix_neither(|&c: &char| c == '=', Precedence(2))
+            .infix_left(|&c: &char| c == '+' || c == '-', Precedence(3))
+            .prefix(|&c: &char| c == '-', Precedence(6))
+            .postfix(|&c: &char| c == '$', Precedence(5))
+            .infix_right(|&c: &char| c == '^', Precedence(7));
+        assert!(matches!(
+            table.query(&'='),
+            Affix::Infix(Precedence(2), Associativity::Neither)
+        ));
+        // The first matching operator wins
+        assert!(matches!(
+            table.query(&'-'),
+            Affix::Infix(Precedence(3), Associativity::Left)
+        ));
+        assert!(matches!(table.query(&'$'), Affix::Postfix(Precedence(5))));
+        assert!(matches!(
+            table.query(&'^'),
+            Affix::Infix(Precedence(7), Associativity::Right)
+        ));
+        assert!(matches!(table.query(&'1'), Affix::Nilfix));
+
+        let table = OperatorTable::default()
+            .register_with(
+                |&c| c == '+',
+                Affix::Infix(Precedence(3), Associativity::Left),
+                "add",
+            )
+            .register_with(|&c| c == '-', Affix::Prefix(Precedence(6)), "neg");
+        assert!(matches!(
+            table.get(&'+'),
+            Some((Affix::Infix(Precedence(3), Associativity::Left), &"add"))
+        ));
+        assert!(matches!(
+            table.get(&'-'),
+            Some((Affix::Prefix(Precedence(6)), &"neg"))
+        ));
+        assert!(table.get(&'1').is_none());
+        assert!(matches!(table.query(&'1'), Affix::Nilfix));
+        assert_eq!(table.validate(), Ok(()));
+
+        let table = OperatorTable::default()
+            .register_with(
+                |&c: &char| c == '*',
+                Affix::Infix(Precedence(4), Associativity::Left),
+                "mul",
+            )
+            .register_with(
+                |&c| c == '+',
+                Affix::Infix(Precedence(3), Associativity::Left),
+                "add",
+            )
+            .register_with(|&c| c == '-', Affix::Prefix(Precedence(3)), "neg")
+            .register_with(
+                |&c| c == '-',
+                Affix::Infix(Precedence(3), Associativity::Right),
+                "sub",
+            );
+        assert_eq!(table.validate(), Err((&"add", &"sub")));
+    }
+
+    #[test]
+    fn non_associative() {
+        assert!(matches!(
+            parse("1=2=3"),
+            Err(PrattError::NonAssociative((3, '='), 3))
+        ));
+        assert!(matches!(
+            parse("1+1=2+2=3"),
+            Err(PrattError::NonAssociative((7, '='), 7))
+        ));
+        assert_eq!(show(&parse("(1=2)=3").unwrap()), "((1=2)=3)");
+        assert_eq!(show(&parse("1=(2=3)").unwrap()), "(1=(2=3))");
+        assert_eq!(show(&parse("1=2?3=4:5").unwrap()), "((1=2)?(3=4):5)");
+
+        // Non-associative operators of the same precedence exclude each other
+        assert!(matches!(
+            parse("1<2=3"),
+            Err(PrattError::NonAssociative((3, '='), 3))
+        ));
+        assert!(matches!(
+            parse("1=2<3"),
+            Err(PrattError::NonAssociative((3, '<'), 3))
+        ));
+        assert_eq!(show(&parse("(1<2)=3").unwrap()), "((1<2)=3)");
+        #[cfg(feature = "alloc")]
+        assert!(matches!(
+            ExprParser.parse_iterative(tokens("1=2<3")),
+            Err(PrattError::NonAssociative((3, '<'), 3))
+        ));
+    }
+
+    #[test]
+    fn saturated_precedence() {
+        // Precedences whose binding powers saturate at `u32::MAX`, where
+        // `raise` gives back the binding power it was called on.
+        struct Saturated(Precedence);
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Saturated {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(self.0, Associativity::Left),
+                    '^' => Affix::Infix(self.0, Associativity::Right),
+                    '=' => Affix::Infix(self.0, Associativity::Neither),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.to_string())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({}{}{})", lhs, op, rhs))
+            }
+
+            fn prefix(&mut self, _: char, _: String) -> Result<String> {
+                Err(NoError)
+            }
+
+            fn postfix(&mut self, _: String, _: char) -> Result<String> {
+                Err(NoError)
+            }
+        }
+
+        for precedence in [Precedence(u32::MAX / 10 + 1), Precedence::max()] {
+            let mut parser = Saturated(precedence);
+            assert_eq!(parser.parse("1+2+3".chars()).unwrap(), "((1+2)+3)");
+            assert_eq!(parser.parse("1^2^3".chars()).unwrap(), "(1^(2^3))");
+            assert!(matches!(
+                parser.parse("1=2=3".chars()),
+                Err(PrattError::NonAssociative('=', ()))
+            ));
+            #[cfg(feature = "alloc")]
+            {
+                let expr = parser.parse_iterative("1+2+3".chars());
+                assert_eq!(expr.unwrap(), "((1+2)+3)");
+                let expr = parser.parse_iterative("1^2^3".chars());
+                assert_eq!(expr.unwrap(), "(1^(2^3))");
+                assert!(matches!(
+                    parser.parse_iterative("1=2=3".chars()),
+                    Err(PrattError::NonAssociative('=', ()))
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn circumfix_operators() {
+        assert_eq!(show(&parse("|1-2|*3").unwrap()), "((|(1-2))*3)");
+        assert_eq!(show(&parse("1+|!2|").unwrap()), "(1+(|(!2)))");
+        assert_eq!(show(&parse("⌈1/2⌉").unwrap()), "(⌈(1/2))");
+        assert_eq!(show(&parse("|⌈1⌉-(|2|)|").unwrap()), "(|((⌈1)-(|2)))");
+        // An opening `|` inside `|...|` is taken as the closing one.
+        assert!(matches!(
+            parse("|1-|2||"),
+            Err(PrattError::MissingOperand((2, '-'), 2))
+        ));
+        assert!(matches!(
+            parse("|1"),
+            Err(PrattError::UnclosedDelimiter((0, '|'), 0))
+        ));
+        assert!(matches!(
+            parse("⌈1"),
+            Err(PrattError::UnclosedDelimiter((0, '⌈'), 0))
+        ));
+    }
+
+    #[test]
+    fn result_alias() {
+        fn halve(i: i64) -> Result<i64, &'static str> {
+            if i % 2 == 0 {
+                Ok(i / 2)
+            } else {
+                Err("odd")
+            }
+        }
+        let ok: Result<i64> = Ok(1);
+        assert!(matches!(ok, Ok(1)));
+        assert_eq!(halve(4), Ok(2));
+        assert_eq!(halve(3), Err("odd"));
+    }
+
+    #[test]
+    fn precedence_const_fns() {
+        const ADD: Precedence = Precedence::new(3);
+        const MUL: Precedence = ADD.raise();
+        assert_eq!(MUL.get(), 4);
+        assert_eq!(MUL.lower().get(), ADD.get());
+        assert!(Precedence::max().raise() == Precedence::max());
+        assert!(Precedence::min().lower() == Precedence::min());
+    }
+
+    /// A self-describing value for deserializing operator tables in tests.
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Int(u32),
+        Str(&'static str),
+        Map(Vec<(&'static str, Value)>),
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::de::IntoDeserializer<'de> for Value {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::Deserializer<'de> for Value {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            visitor: V,
+        ) -> core::result::Result<V::Value, Self::Error> {
+            match self {
+                Value::Int(i) => visitor.visit_u32(i),
+                Value::Str(s) => visitor.visit_borrowed_str(s),
+                Value::Map(m) => {
+                    visitor.visit_map(serde::de::value::MapDeserializer::new(m.into_iter()))
+                }
+            }
+        }
+
+        fn deserialize_enum<V: serde::de::Visitor<'de>>(
+            self,
+            name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> core::result::Result<V::Value, Self::Error> {
+            use serde::de::value::{MapAccessDeserializer, MapDeserializer, StrDeserializer};
+            match self {
+                Value::Map(m) => MapAccessDeserializer::new(MapDeserializer::new(m.into_iter()))
+                    .deserialize_enum(name, variants, visitor),
+                Value::Str(s) => StrDeserializer::new(s).deserialize_enum(name, variants, visitor),
+                Value::Int(_) => self.deserialize_any(visitor),
+            }
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct identifier ignored_any
+        }
+    }
+
+    /// Serializes the subset of the data model used by operator tables.
+    #[cfg(feature = "serde")]
+    struct ValueSerializer;
+
+    #[cfg(feature = "serde")]
+    struct StructVariant(&'static str, Vec<(&'static str, Value)>);
+
+    #[cfg(feature = "serde")]
+    impl serde::ser::SerializeStructVariant for StructVariant {
+        type Ok = Value;
+        type Error = serde::de::value::Error;
+
+        fn serialize_field<T: serde::Serialize + ?Sized>(
+            &mut self,
+            key: &'static str,
+            value: &T,
+        ) -> core::result::Result<(), Self::Error> {
+            self.1.push((key, value.serialize(ValueSerializer)?));
+            Ok(())
+        }
+
+        fn end(self) -> core::result::Result<Value, Self::Error> {
+            Ok(Value::Map(std::vec![(self.0, Value::Map(self.1))]))
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl serde::Serializer for ValueSerializer {
+        type Ok = Value;
+        type Error = serde::de::value::Error;
+        type SerializeSeq = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeTuple = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeTupleStruct = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeTupleVariant = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeMap = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeStruct = serde::ser::Impossible<Value, Self::Error>;
+        type SerializeStructVariant = StructVariant;
+
+        fn serialize_u32(self, v: u32) -> core::result::Result<Value, Self::Error> {
+            Ok(Value::Int(v))
+        }
+
+        fn serialize_u64(self, v: u64) -> core::result::Result<Value, Self::Error> {
+            Ok(Value::Int(v as u32))
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+        ) -> core::result::Result<Value, Self::Error> {
+            Ok(Value::Str(variant))
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            variant: &'static str,
+            _: usize,
+        ) -> core::result::Result<StructVariant, Self::Error> {
+            Ok(StructVariant(variant, Vec::new()))
+        }
+
+        fn serialize_bool(self, _: bool) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_i8(self, _: i8) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_i16(self, _: i16) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_i32(self, _: i32) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_i64(self, _: i64) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_u8(self, _: u8) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_u16(self, _: u16) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_f32(self, _: f32) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_f64(self, _: f64) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_char(self, _: char) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_str(self, _: &str) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_bytes(self, _: &[u8]) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn collect_str<T: core::fmt::Display + ?Sized>(
+            self,
+            _: &T,
+        ) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_none(self) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_some<T: serde::Serialize + ?Sized>(
+            self,
+            _: &T,
+        ) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_unit(self) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_unit_struct(
+            self,
+            _: &'static str,
+        ) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            _: &T,
+        ) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> core::result::Result<Value, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_seq(
+            self,
+            _: Option<usize>,
+        ) -> core::result::Result<Self::SerializeSeq, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_tuple(
+            self,
+            _: usize,
+        ) -> core::result::Result<Self::SerializeTuple, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_tuple_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> core::result::Result<Self::SerializeTupleStruct, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_tuple_variant(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: usize,
+        ) -> core::result::Result<Self::SerializeTupleVariant, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_map(
+            self,
+            _: Option<usize>,
+        ) -> core::result::Result<Self::SerializeMap, Self::Error> {
+            unimplemented!()
+        }
+        fn serialize_struct(
+            self,
+            _: &'static str,
+            _: usize,
+        ) -> core::result::Result<Self::SerializeStruct, Self::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        use serde::{Deserialize, Serialize};
+
+        let table = [
+            Affix::Nilfix,
+            Affix::Infix(Precedence(2), Associativity::Neither),
+            Affix::Infix(Precedence(3), Associativity::Left),
+            Affix::Infix(Precedence(7), Associativity::Right),
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(Precedence(3)),
+            Affix::Prefix(Precedence(6)),
+            Affix::PrefixPrimary(Precedence(6)),
+            Affix::Postfix(Precedence(5)),
+            Affix::PostfixWith(Precedence(8)),
+            Affix::PostfixOrInfix(Precedence(4), Associativity::Neither),
+            Affix::PostfixOrInfix(Precedence(4), Associativity::Left),
+            Affix::PostfixOrInfix(Precedence(4), Associativity::Right),
+            Affix::Ternary(Precedence(1), Associativity::Right),
+            Affix::Circumfix,
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(Precedence(1), 2),
+        ];
+        for affix in table {
+            let value = affix.serialize(ValueSerializer).unwrap();
+            let again = Affix::<Precedence>::deserialize(value).unwrap();
+            assert_eq!(
+                again.serialize(ValueSerializer).unwrap(),
+                affix.serialize(ValueSerializer).unwrap()
+            );
+            assert!(again.lbp() == affix.lbp() && again.nbp() == affix.nbp());
+        }
+        assert_eq!(
+            Affix::Infix(Precedence(3), Associativity::Left)
+                .serialize(ValueSerializer)
+                .unwrap(),
+            Value::Map(std::vec![(
+                "infix",
+                Value::Map(std::vec![
+                    ("prec", Value::Int(3)),
+                    ("assoc", Value::Str("left"))
+                ]),
+            )])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize() {
+        use serde::Deserialize;
+
+        let affix = |value: Value| Affix::<Precedence>::deserialize(value).unwrap();
+        let infix = Value::Map(std::vec![(
+            "infix",
+            Value::Map(std::vec![
+                ("prec", Value::Int(3)),
+                ("assoc", Value::Str("left"))
+            ]),
+        )]);
+        assert!(matches!(
+            affix(infix),
+            Affix::Infix(Precedence(3), Associativity::Left)
+        ));
+        let prefix = Value::Map(std::vec![(
+            "prefix",
+            Value::Map(std::vec![("prec", Value::Int(6))])
+        )]);
+        assert!(matches!(affix(prefix), Affix::Prefix(Precedence(6))));
+        assert!(matches!(affix(Value::Str("nilfix")), Affix::Nilfix));
+        assert!(matches!(affix(Value::Str("circumfix")), Affix::Circumfix));
+        #[cfg(feature = "alloc")]
+        {
+            let mixfix = Value::Map(std::vec![(
+                "mixfix",
+                Value::Map(std::vec![
+                    ("prec", Value::Int(1)),
+                    ("separators", Value::Int(2))
+                ]),
+            )]);
+            assert!(matches!(affix(mixfix), Affix::Mixfix(Precedence(1), 2)));
+        }
+
+        let unknown = Value::Map(std::vec![(
+            "infix",
+            Value::Map(std::vec![("prec", Value::Int(3))])
+        )]);
+        assert!(Affix::<Precedence>::deserialize(unknown).is_err());
+    }
+
+    #[test]
+    fn affix_binding_powers() {
+        let left = Affix::Infix(Precedence(3), Associativity::Left);
+        assert!(left.lbp() == Precedence(30));
+        assert!(left.nbp() == Precedence(31));
+        assert!(left.rbp() == Some(Precedence(30)));
+
+        let right = Affix::Infix(Precedence(7), Associativity::Right);
+        assert!(right.lbp() == Precedence(70));
+        assert!(right.nbp() == Precedence(71));
+        assert!(right.rbp() == Some(Precedence(69)));
+
+        let neither = Affix::Infix(Precedence(2), Associativity::Neither);
+        assert!(neither.lbp() == neither.nbp());
+
+        let prefix = Affix::Prefix(Precedence(6));
+        assert!(prefix.lbp() == Precedence::MIN);
+        assert!(prefix.nbp() == Precedence::MAX);
+        assert!(prefix.rbp() == Some(Precedence(59)));
+
+        assert!(Affix::Postfix(Precedence(5)).lbp() == Precedence(50));
+        assert!(Affix::<Precedence>::Postfix(Precedence(5)).rbp().is_none());
+        assert!(Affix::<Precedence>::Nilfix.rbp().is_none());
+
+        // The parser's binding powers follow its own `normalize`
+        let mut parser = Custom(Dense);
+        let lbp = PrattParser::<std::vec::IntoIter<Token>, _>::lbp(&mut parser, left);
+        assert!(lbp != left.lbp());
+    }
+
+    #[test]
+    fn binding_powers() {
+        let binding_powers = |c| {
+            PrattParser::<std::vec::IntoIter<Token>>::binding_powers(&mut ExprParser, &(0, c))
+                .unwrap()
+        };
+        let p = Precedence;
+        assert!(binding_powers('+') == (p(30), Some(p(30)), p(31)));
+        assert!(binding_powers('^') == (p(70), Some(p(69)), p(71)));
+        assert!(binding_powers('=') == (p(20), Some(p(21)), p(20)));
+        assert!(binding_powers('!') == (Precedence::MIN, Some(p(59)), Precedence::MAX));
+        assert!(binding_powers('$') == (p(50), None, Precedence::MAX));
+        assert!(binding_powers('1') == (Precedence::MIN, None, Precedence::MAX));
+    }
+
+    impl unparse::PrattUnparse for Expr {
+        fn node(&self) -> unparse::Node<'_, Self> {
+            use unparse::Node;
+            let affix = |op: &char| {
+                PrattParser::<std::vec::IntoIter<Token>>::query(&mut ExprParser, &(0, *op)).unwrap()
+            };
+            match self {
+                Expr::BinOp(lhs, op, rhs) => match affix(op) {
+                    Affix::Infix(precedence, associativity) => {
+                        Node::Infix(&**lhs, op, precedence, associativity, &**rhs)
+                    }
+                    _ => unreachable!(),
+                },
+                Expr::UnOp(op, operand) => match affix(op) {
+                    Affix::Prefix(precedence) => Node::Prefix(op, precedence, &**operand),
+                    Affix::Postfix(precedence) => Node::Postfix(&**operand, op, precedence),
+                    _ => unreachable!(),
+                },
+                Expr::Int(i) => Node::Nilfix(i),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn unparse() {
+        use unparse::PrattUnparse;
+
+        for (input, output) in [
+            ("(1+2)*3", "(1 + 2) * 3"),
+            ("1+2+3", "1 + 2 + 3"),
+            ("1+(2+3)", "1 + (2 + 3)"),
+            ("1-(2*3)", "1 - 2 * 3"),
+            ("2^3^4", "2 ^ 3 ^ 4"),
+            ("(2^3)^4", "(2 ^ 3) ^ 4"),
+            ("(1=2)=3", "(1 = 2) = 3"),
+            ("!1$", "!1$"),
+            ("!(1$)", "!(1$)"),
+            ("1+!2*3", "1 + !2 * 3"),
+            ("(1+!2)*3", "(1 + !2) * 3"),
+            ("1*!(2+3)", "1 * !(2 + 3)"),
+            ("(!2^3)$", "!2 ^ 3$"),
+        ] {
+            let expr = parse(input).unwrap();
+            let unparsed = expr.unparse().to_string();
+            assert_eq!(unparsed, output, "{}", input);
+            assert_eq!(parse(&unparsed).unwrap(), expr, "{}", input);
+        }
+    }
+
+    /// An expression which borrows its names from the source.
+    #[derive(Debug, PartialEq)]
+    enum Borrowed<'a> {
+        Call(&'a str, Box<Borrowed<'a>>),
+        Add(Box<Borrowed<'a>>, Box<Borrowed<'a>>),
+        Name(&'a str),
+    }
+
+    struct BorrowingParser;
+
+    impl<'a, I> PrattParser<I> for BorrowingParser
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        type Error = NoError;
+        type Input = &'a str;
+        type Output = Borrowed<'a>;
+        type Position = ();
+
+        fn query(&mut self, word: &&'a str) -> Result<Affix> {
+            Ok(match *word {
+                "+" => Affix::Infix(Precedence(1), Associativity::Left),
+                word if word.ends_with(':') => Affix::Prefix(Precedence(2)),
+                _ => Affix::Nilfix,
+            })
+        }
+
+        fn primary(&mut self, word: &'a str, _: &mut Tail<'_, I>) -> Result<Borrowed<'a>> {
+            Ok(Borrowed::Name(word))
+        }
+
+        fn infix(
+            &mut self,
+            lhs: Borrowed<'a>,
+            _: &'a str,
+            rhs: Borrowed<'a>,
+        ) -> Result<Borrowed<'a>> {
+            Ok(Borrowed::Add(Box::new(lhs), Box::new(rhs)))
+        }
+
+        fn prefix(&mut self, word: &'a str, rhs: Borrowed<'a>) -> Result<Borrowed<'a>> {
+            Ok(Borrowed::Call(&word[..word.len() - 1], Box::new(rhs)))
+        }
+
+        fn postfix(&mut self, _: Borrowed<'a>, _: &'a str) -> Result<Borrowed<'a>> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn borrowed_output() {
+        let source = String::from("f: x + y");
+        let expr = BorrowingParser.parse(source.split(' ')).unwrap();
+        assert_eq!(
+            expr,
+            Borrowed::Add(
+                Box::new(Borrowed::Call("f", Box::new(Borrowed::Name("x")))),
+                Box::new(Borrowed::Name("y"))
+            )
+        );
+        match expr {
+            Borrowed::Add(_, rhs) => match *rhs {
+                Borrowed::Name(name) => assert!(core::ptr::eq(name, &source[7..])),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse_all() {
+        let parse_all = |input: &str, trailing| {
+            let mut inputs = tokens(input).into_iter().peekable();
+            let exprs = ExprParser.parse_all(&mut inputs, |&(_, c)| c == ',', trailing);
+            let exprs = exprs.map(|exprs| exprs.iter().map(show).collect::<Vec<_>>());
+            (exprs, inputs.next())
+        };
+        let (exprs, next) = parse_all("1+2, 3, (4*5)", false);
+        assert_eq!(exprs.unwrap(), ["(1+2)", "3", "(4*5)"]);
+        assert_eq!(next, None);
+        let (exprs, next) = parse_all("", false);
+        assert!(exprs.unwrap().is_empty());
+        assert_eq!(next, None);
+        let (exprs, next) = parse_all(";1", false);
+        assert!(exprs.unwrap().is_empty());
+        assert_eq!(next, Some((0, ';')));
+        let (exprs, next) = parse_all("1,2;3", false);
+        assert_eq!(exprs.unwrap(), ["1", "2"]);
+        assert_eq!(next, Some((3, ';')));
+        assert_eq!(parse_all("1,2,", true).0.unwrap(), ["1", "2"]);
+        assert!(matches!(
+            parse_all("1,2,", false).0,
+            Err(PrattError::EmptyInput)
+        ));
+        assert!(matches!(
+            parse_all("1,,2", true).0,
+            Err(PrattError::EmptyInput)
+        ));
+
+        // Statement lists, whose separator also ends an expression
+        let mut inputs = tokens("1+2;3;(4;5)").into_iter().peekable();
+        let exprs = ExprParser.parse_all(&mut inputs, |&(_, c)| c == ';', false);
+        assert!(matches!(
+            exprs,
+            Err(PrattError::UnclosedDelimiter((6, '('), _))
+        ));
+        let mut inputs = tokens("1+2;3;").into_iter().peekable();
+        let exprs = ExprParser.parse_all(&mut inputs, |&(_, c)| c == ';', true);
+        assert_eq!(
+            exprs.unwrap().iter().map(show).collect::<Vec<_>>(),
+            ["(1+2)", "3"]
+        );
+    }
+
+    /// Records the events of a visit, and evaluates `+`, `*` and `~` on a
+    /// stack.
+    #[derive(Default)]
+    struct Events {
+        events: Vec<String>,
+        stack: Vec<i64>,
+    }
+
+    impl<I: Iterator<Item = Token>> visit::PrattVisitor<I> for Events {
+        type Error = NoError;
+        type Input = Token;
+        type Position = usize;
+
+        fn query(&mut self, input: &Token) -> Result<Affix> {
+            PrattParser::<I>::query(&mut ExprParser, input)
+        }
+
+        fn primary(&mut self, (_, c): Token) -> Result<()> {
+            self.events.push(std::format!("primary {}", c));
+            self.stack.push(c.to_digit(10).ok_or(NoError)? as i64);
+            Ok(())
+        }
+
+        fn infix_open(&mut self, (_, op): &Token) -> Result<()> {
+            self.events.push(std::format!("open {}", op));
+            Ok(())
+        }
+
+        fn infix(&mut self, (_, op): Token) -> Result<()> {
+            self.events.push(std::format!("infix {}", op));
+            let rhs = self.stack.pop().unwrap();
+            let lhs = self.stack.pop().unwrap();
+            self.stack
+                .push(if op == '+' { lhs + rhs } else { lhs * rhs });
+            Ok(())
+        }
+
+        fn prefix(&mut self, (_, op): Token) -> Result<()> {
+            self.events.push(std::format!("prefix {}", op));
+            let rhs = self.stack.pop().unwrap();
+            self.stack.push(-rhs);
+            Ok(())
+        }
+
+        fn postfix(&mut self, (_, op): Token) -> Result<()> {
+            self.events.push(std::format!("postfix {}", op));
+            Ok(())
+        }
+
+        fn position(&self, &(position, _): &Token) -> usize {
+            position
+        }
+    }
+
+    #[test]
+    fn visit() {
+        use visit::PrattVisitor;
+
+        let mut events = Events::default();
+        events.visit(tokens("1+2*3")).unwrap();
+        assert_eq!(
+            events.events,
+            [
+                "primary 1",
+                "open +",
+                "primary 2",
+                "open *",
+                "primary 3",
+                "infix *",
+                "infix +",
+            ]
+        );
+        assert_eq!(events.stack, [7]);
+
+        let mut events = Events::default();
+        events.visit(tokens("~2*3+4$")).unwrap();
+        assert_eq!(
+            events.events,
+            [
+                "primary 2",
+                "prefix ~",
+                "open *",
+                "primary 3",
+                "infix *",
+                "open +",
+                "primary 4",
+                "postfix $",
+                "infix +",
+            ]
+        );
+        assert_eq!(events.stack, [-2]);
+
+        let mut events = Events::default();
+        let result = events.visit(tokens("1+*"));
+        assert!(matches!(
+            result,
+            Err(PrattError::UnexpectedInfix((2, '*'), 2))
+        ));
+
+        // Operators with more than one operand fail before visiting any
+        for (input, op, position) in [("1?2:3", '?', 1), ("(1)", '(', 0), ("1+2[3]", '[', 3)] {
+            let mut events = Events::default();
+            let error = events.visit(tokens(input)).unwrap_err();
+            assert!(matches!(error, PrattError::UnsupportedAffix((_, c), _) if c == op));
+            assert_eq!(error.position(), Some(&position), "{}", input);
+            assert!(!events.events.iter().any(|event| event.ends_with(op)));
+        }
+    }
+
+    #[test]
+    fn override_nud() {
+        // Reads `-` directly in front of a digit as part of a literal, and
+        // leaves every other input to the default `nud`.
+        struct Literals;
+
+        impl<I: Iterator<Item = char>> PrattParser<I> for Literals {
+            type Error = NoError;
+            type Input = char;
+            type Output = String;
+            type Position = ();
+
+            fn query(&mut self, c: &char) -> Result<Affix> {
+                Ok(match c {
+                    '+' => Affix::Infix(Precedence(2), Associativity::Left),
+                    '*' => Affix::Infix(Precedence(3), Associativity::Left),
+                    '-' => Affix::Prefix(Precedence(1)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, c: char, _: &mut Tail<'_, I>) -> Result<String> {
+                Ok(c.to_string())
+            }
+
+            fn infix(&mut self, lhs: String, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({} {} {})", op, lhs, rhs))
+            }
+
+            fn prefix(&mut self, op: char, rhs: String) -> Result<String> {
+                Ok(std::format!("({} {})", op, rhs))
+            }
+
+            fn postfix(&mut self, lhs: String, _: char) -> Result<String> {
+                Ok(lhs)
+            }
+
+            fn nud(
+                &mut self,
+                head: char,
+                tail: &mut Tail<'_, I>,
+                info: Affix,
+            ) -> core::result::Result<String, PrattError<char, NoError>> {
+                if head == '-' {
+                    if let Some(digit) = tail.next_if(|c| c.is_ascii_digit()) {
+                        return Ok(std::format!("-{}", digit));
+                    }
+                }
+                crate::nud(self, head, tail, info)
+            }
+        }
+
+        let parse = |input: &str| Literals.parse(input.chars()).unwrap();
+        assert_eq!(parse("-2*3"), "(* -2 3)");
+        assert_eq!(parse("--2*3"), "(- (* -2 3))");
+        assert_eq!(parse("1+-2"), "(+ 1 -2)");
+
+        // The other parses leave `-` to the default `nud`
+        #[cfg(feature = "alloc")]
+        {
+            let expr = Literals.parse_iterative("-2*3".chars());
+            assert_eq!(expr.unwrap(), "(- (* 2 3))");
+            let (expr, errors) = Literals.parse_all_errors("-2*3".chars());
+            assert_eq!(expr.unwrap(), "(- (* 2 3))");
+            assert!(errors.is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn precedence_table() {
+        let mut table = levels::PrecedenceTable::new();
+        let sum = table.level("sum").unwrap();
+        let product = table.level("product").unwrap();
+        let power = table.level("power").unwrap();
+        let unary = levels::PrecedenceTable::between(product, power).unwrap();
+        assert!(sum < product && product < unary && unary < power);
+        assert!(table.get("sum") == Some(sum));
+        assert!(table.get("unary").is_none());
+
+        // Levels are still far apart once the parser normalizes them.
+        assert!(unary.normalize() < power.normalize());
+
+        // Adjacent or reversed precedences have none between them.
+        assert!(levels::PrecedenceTable::between(sum, sum.raise()).is_none());
+        assert!(levels::PrecedenceTable::between(power, sum).is_none());
+        assert!(levels::PrecedenceTable::between(sum, sum).is_none());
+
+        // No level fits above the strongest precedence.
+        assert!(levels::above(Precedence::max()).is_none());
+    }
+
+    #[test]
+    fn unsupported_affix() {
+        /// Classifies inputs which it has no constructors for.
+        struct Partial {
+            juxtaposition: Option<Precedence>,
+        }
+
+        impl<I: Iterator<Item = Token>> PrattParser<I> for Partial {
+            type Error = NoError;
+            type Input = Token;
+            type Output = char;
+            type Position = usize;
+
+            fn query(&mut self, &(_, c): &Token) -> Result<Affix> {
+                Ok(match c {
+                    '?' => Affix::Ternary(Precedence(1), Associativity::Right),
+                    '(' => Affix::Circumfix,
+                    '[' => Affix::PostfixWith(Precedence(2)),
+                    _ => Affix::Nilfix,
+                })
+            }
+
+            fn primary(&mut self, (_, c): Token, _: &mut Tail<'_, I>) -> Result<char> {
+                Ok(c)
+            }
+
+            fn infix(&mut self, _: char, _: Token, _: char) -> Result<char> {
+                unreachable!()
+            }
+
+            fn prefix(&mut self, _: Token, _: char) -> Result<char> {
+                unreachable!()
+            }
+
+            fn postfix(&mut self, _: char, _: Token) -> Result<char> {
+                unreachable!()
+            }
+
+            fn juxtaposition(&self) -> Option<Precedence> {
+                self.juxtaposition
+            }
+
+            fn closes(&(_, op): &Token, &(_, delimiter): &Token) -> bool {
+                matches!((op, delimiter), ('?', ':') | ('(', ')'))
+            }
+
+            fn position(&self, &(position, _): &Token) -> usize {
+                position
+            }
+        }
+
+        let tokens = |input: &'static str| input.chars().enumerate();
+        let mut parser = Partial {
+            juxtaposition: None,
+        };
+        for (input, position) in [("a?b:c", 1), ("(a)", 0), ("a[b]", 1)] {
+            let error = parser.parse(tokens(input)).unwrap_err();
+            assert!(matches!(error, PrattError::UnsupportedAffix(_, p) if p == position));
+            assert!(error.position() == Some(&position));
+        }
+        #[cfg(feature = "alloc")]
+        assert!(matches!(
+            parser.parse_iterative(tokens("a?b:c")),
+            Err(PrattError::UnsupportedAffix((1, '?'), 1))
+        ));
+
+        parser.juxtaposition = Some(Precedence(3));
+        assert!(matches!(
+            parser.parse(tokens("fx")),
+            Err(PrattError::UnsupportedJuxtaposition)
+        ));
     }
 }
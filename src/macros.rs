@@ -0,0 +1,94 @@
+//! A shorthand for the operator table of [`PrattParser::query`].
+//!
+//! [`PrattParser::query`]: crate::PrattParser::query
+
+/// Classifies an input by a table of patterns and affixes, and evaluates to
+/// the `Affix` of the first pattern which matches.
+///
+/// Precedences are written as numbers and associativities by name, e.g.
+/// `Infix(3, Left)` for `Affix::Infix(Precedence(3), Associativity::Left)`.
+/// An arm may also evaluate a block instead, e.g. to `return` an error.
+///
+/// Arms starting with `led` only apply after an operand, i.e. in
+/// [`PrattParser::query_led`], and are then tried before all other arms.
+/// Whether the input follows an operand is given after the input, so one
+/// table can tell a negation `-` from a subtraction:
+///
+/// ```
+/// use pratt::{operators, Affix};
+///
+/// fn affix(op: &str, led: bool) -> Affix {
+///     operators!(op, led;
+///         "+" => Infix(3, Left),
+///         "-" => Prefix(6),
+///         led "-" => Infix(3, Left),
+///         "^" => Infix(7, Right),
+///         "(" => Circumfix,
+///         _ => Nilfix,
+///     )
+/// }
+///
+/// assert!(matches!(affix("-", false), Affix::Prefix(_)));
+/// assert!(matches!(affix("-", true), Affix::Infix(..)));
+/// ```
+///
+/// Without it, as in `operators!(op; ...)`, the table has no `led` arms.
+///
+/// [`PrattParser::query_led`]: crate::PrattParser::query_led
+#[macro_export]
+macro_rules! operators {
+    (@munch $input:expr, $led:expr, [$($after:tt)*] [$($arms:tt)*]) => {
+        $crate::operators!(@emit $input, $led, [$($after)*] [$($arms)*])
+    };
+    (@munch $input:expr, $led:expr, [$($after:tt)*] [$($arms:tt)*]
+        led $pat:pat => $block:block $(, $($rest:tt)*)?) => {
+        $crate::operators!(@munch $input, $led, [$($after)* ($pat) [$block]] [$($arms)*]
+            $($($rest)*)?)
+    };
+    (@munch $input:expr, $led:expr, [$($after:tt)*] [$($arms:tt)*]
+        led $pat:pat => $name:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::operators!(@munch $input, $led, [$($after)* ($pat) [$name $(($($arg)*))?]]
+            [$($arms)*] $($($rest)*)?)
+    };
+    (@munch $input:expr, $led:expr, [$($after:tt)*] [$($arms:tt)*]
+        $pat:pat => $block:block $(, $($rest:tt)*)?) => {
+        $crate::operators!(@munch $input, $led, [$($after)*] [$($arms)* ($pat) [$block]]
+            $($($rest)*)?)
+    };
+    (@munch $input:expr, $led:expr, [$($after:tt)*] [$($arms:tt)*]
+        $pat:pat => $name:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::operators!(@munch $input, $led, [$($after)*]
+            [$($arms)* ($pat) [$name $(($($arg)*))?]] $($($rest)*)?)
+    };
+    (@emit $input:expr, $led:expr,
+        [$(($after:pat) [$($after_affix:tt)*])*] [$(($pat:pat) [$($affix:tt)*])*]) => {
+        match $input {
+            $($after if $led => $crate::operators!(@affix $($after_affix)*),)*
+            $($pat => $crate::operators!(@affix $($affix)*),)*
+        }
+    };
+    (@affix $block:block) => {
+        $block
+    };
+    (@affix $name:ident) => {
+        $crate::Affix::$name
+    };
+    (@affix Mixfix($precedence:expr, $separators:expr)) => {
+        $crate::Affix::Mixfix($crate::Precedence($precedence), $separators)
+    };
+    (@affix $name:ident($precedence:expr)) => {
+        $crate::Affix::$name($crate::Precedence($precedence))
+    };
+    (@affix $name:ident($precedence:expr, $associativity:ident)) => {
+        $crate::Affix::$name(
+            $crate::Precedence($precedence),
+            $crate::Associativity::$associativity,
+        )
+    };
+    ($input:expr, $led:expr; $($arms:tt)*) => {
+        $crate::operators!(@munch $input, $led, [] [] $($arms)*)
+    };
+    ($input:expr; $($arms:tt)*) => {
+        $crate::operators!(@munch $input, false, [] [] $($arms)*)
+    };
+}
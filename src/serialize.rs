@@ -0,0 +1,100 @@
+//! Serialization of operator tables, behind the `serde` feature.
+//!
+//! `Affix` is written with named fields, e.g. `Affix::Infix(Precedence(3),
+//! Associativity::Left)` as `{ "infix": { "prec": 3, "assoc": "left" } }`
+//! and `Affix::Nilfix` as `"nilfix"`.
+
+use crate::{Affix, Associativity};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AffixDef<P> {
+    Nilfix,
+    Infix {
+        prec: P,
+        assoc: Associativity,
+    },
+    #[cfg(feature = "alloc")]
+    InfixChain {
+        prec: P,
+    },
+    Prefix {
+        prec: P,
+    },
+    PrefixPrimary {
+        prec: P,
+    },
+    Postfix {
+        prec: P,
+    },
+    PostfixWith {
+        prec: P,
+    },
+    PostfixOrInfix {
+        prec: P,
+        assoc: Associativity,
+    },
+    Ternary {
+        prec: P,
+        assoc: Associativity,
+    },
+    Circumfix,
+    #[cfg(feature = "alloc")]
+    Mixfix {
+        prec: P,
+        separators: usize,
+    },
+}
+
+impl<P> From<Affix<P>> for AffixDef<P> {
+    fn from(affix: Affix<P>) -> Self {
+        match affix {
+            Affix::Nilfix => AffixDef::Nilfix,
+            Affix::Infix(prec, assoc) => AffixDef::Infix { prec, assoc },
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(prec) => AffixDef::InfixChain { prec },
+            Affix::Prefix(prec) => AffixDef::Prefix { prec },
+            Affix::PrefixPrimary(prec) => AffixDef::PrefixPrimary { prec },
+            Affix::Postfix(prec) => AffixDef::Postfix { prec },
+            Affix::PostfixWith(prec) => AffixDef::PostfixWith { prec },
+            Affix::PostfixOrInfix(prec, assoc) => AffixDef::PostfixOrInfix { prec, assoc },
+            Affix::Ternary(prec, assoc) => AffixDef::Ternary { prec, assoc },
+            Affix::Circumfix => AffixDef::Circumfix,
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(prec, separators) => AffixDef::Mixfix { prec, separators },
+        }
+    }
+}
+
+impl<P> From<AffixDef<P>> for Affix<P> {
+    fn from(affix: AffixDef<P>) -> Self {
+        match affix {
+            AffixDef::Nilfix => Affix::Nilfix,
+            AffixDef::Infix { prec, assoc } => Affix::Infix(prec, assoc),
+            #[cfg(feature = "alloc")]
+            AffixDef::InfixChain { prec } => Affix::InfixChain(prec),
+            AffixDef::Prefix { prec } => Affix::Prefix(prec),
+            AffixDef::PrefixPrimary { prec } => Affix::PrefixPrimary(prec),
+            AffixDef::Postfix { prec } => Affix::Postfix(prec),
+            AffixDef::PostfixWith { prec } => Affix::PostfixWith(prec),
+            AffixDef::PostfixOrInfix { prec, assoc } => Affix::PostfixOrInfix(prec, assoc),
+            AffixDef::Ternary { prec, assoc } => Affix::Ternary(prec, assoc),
+            AffixDef::Circumfix => Affix::Circumfix,
+            #[cfg(feature = "alloc")]
+            AffixDef::Mixfix { prec, separators } => Affix::Mixfix(prec, separators),
+        }
+    }
+}
+
+impl<P: Copy + Serialize> Serialize for Affix<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AffixDef::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de, P: Deserialize<'de>> Deserialize<'de> for Affix<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        AffixDef::deserialize(deserializer).map(Affix::from)
+    }
+}
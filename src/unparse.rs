@@ -0,0 +1,151 @@
+//! Printing outputs back as inputs, with only the parentheses which are
+//! needed to parse them again.
+
+use crate::{Affix, Associativity, BindingPower, Precedence};
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+
+/// A node of an output tree, split into its operator and operands.
+pub enum Node<'a, T: ?Sized, P = Precedence> {
+    Nilfix(&'a dyn Display),
+    Prefix(&'a dyn Display, P, &'a T),
+    Postfix(&'a T, &'a dyn Display, P),
+    Infix(&'a T, &'a dyn Display, P, Associativity, &'a T),
+}
+
+impl<T: ?Sized, P: Copy> Node<'_, T, P> {
+    fn affix(&self) -> Affix<P> {
+        match *self {
+            Node::Nilfix(_) => Affix::Nilfix,
+            Node::Prefix(_, precedence, _) => Affix::Prefix(precedence),
+            Node::Postfix(_, _, precedence) => Affix::Postfix(precedence),
+            Node::Infix(_, _, precedence, associativity, _) => {
+                Affix::Infix(precedence, associativity)
+            }
+        }
+    }
+}
+
+/// Outputs which can be printed back, see [`PrattUnparse::unparse`].
+pub trait PrattUnparse<P: BindingPower = Precedence> {
+    /// Splits this node into its operator and operands.
+    fn node(&self) -> Node<'_, Self, P>;
+
+    /// Displays this node with infix operators surrounded by spaces, and
+    /// with parentheses around exactly those operands which would otherwise
+    /// be parsed differently, e.g. `(1 + 2) * 3` but `1 + 2 + 3`.
+    fn unparse(&self) -> Unparsed<'_, Self, P> {
+        Unparsed {
+            node: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Displays a node, see [`PrattUnparse::unparse`].
+pub struct Unparsed<'a, T: ?Sized, P> {
+    node: &'a T,
+    marker: PhantomData<P>,
+}
+
+impl<T: PrattUnparse<P> + ?Sized, P: BindingPower> Display for Unparsed<'_, T, P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.node.node() {
+            Node::Nilfix(primary) => write!(f, "{}", primary),
+            Node::Prefix(op, precedence, rhs) => {
+                write!(f, "{}", op)?;
+                let rbp = Affix::Prefix(precedence).rbp().unwrap();
+                operand(rhs, needs_parens_right(rhs, rbp), f)
+            }
+            Node::Postfix(lhs, op, precedence) => {
+                let lbp = Affix::Postfix(precedence).lbp();
+                operand(lhs, needs_parens_left(lhs, lbp), f)?;
+                write!(f, "{}", op)
+            }
+            Node::Infix(lhs, op, precedence, associativity, rhs) => {
+                let affix = Affix::Infix(precedence, associativity);
+                operand(lhs, needs_parens_left(lhs, affix.lbp()), f)?;
+                write!(f, " {} ", op)?;
+                operand(rhs, needs_parens_right(rhs, affix.rbp().unwrap()), f)
+            }
+        }
+    }
+}
+
+fn operand<T, P>(node: &T, parens: bool, f: &mut Formatter<'_>) -> fmt::Result
+where
+    T: PrattUnparse<P> + ?Sized,
+    P: BindingPower,
+{
+    if parens {
+        write!(f, "({})", node.unparse())
+    } else {
+        write!(f, "{}", node.unparse())
+    }
+}
+
+/// Whether `node` must be parenthesized as the left operand of an operator
+/// with left binding power `lbp`: either an operator on its right edge would
+/// take the operator as part of its own operand, or the operator may not
+/// follow `node` at all.
+fn needs_parens_left<T, P>(node: &T, lbp: P) -> bool
+where
+    T: PrattUnparse<P> + ?Sized,
+    P: BindingPower,
+{
+    let affix = node.node().affix();
+    lbp >= affix.nbp() || lowest_rbp(node).is_some_and(|rbp| rbp < lbp)
+}
+
+/// Whether `node` must be parenthesized as the right operand of an operator
+/// with right binding power `rbp`: an operator on its left edge would not
+/// bind tighter than `rbp`.
+fn needs_parens_right<T, P>(node: &T, rbp: P) -> bool
+where
+    T: PrattUnparse<P> + ?Sized,
+    P: BindingPower,
+{
+    lowest_lbp(node).is_some_and(|lbp| lbp <= rbp)
+}
+
+/// The lowest right binding power of the operators on the right edge of
+/// `node` as printed.
+fn lowest_rbp<T, P>(node: &T) -> Option<P>
+where
+    T: PrattUnparse<P> + ?Sized,
+    P: BindingPower,
+{
+    let (affix, rhs) = match node.node() {
+        Node::Prefix(_, precedence, rhs) => (Affix::Prefix(precedence), rhs),
+        Node::Infix(_, _, precedence, associativity, rhs) => {
+            (Affix::Infix(precedence, associativity), rhs)
+        }
+        Node::Nilfix(_) | Node::Postfix(..) => return None,
+    };
+    let rbp = affix.rbp().unwrap();
+    match lowest_rbp(rhs) {
+        Some(inner) if !needs_parens_right(rhs, rbp) => Some(rbp.min(inner)),
+        _ => Some(rbp),
+    }
+}
+
+/// The lowest left binding power of the operators on the left edge of
+/// `node` as printed.
+fn lowest_lbp<T, P>(node: &T) -> Option<P>
+where
+    T: PrattUnparse<P> + ?Sized,
+    P: BindingPower,
+{
+    let (affix, lhs) = match node.node() {
+        Node::Postfix(lhs, _, precedence) => (Affix::Postfix(precedence), lhs),
+        Node::Infix(lhs, _, precedence, associativity, _) => {
+            (Affix::Infix(precedence, associativity), lhs)
+        }
+        Node::Nilfix(_) | Node::Prefix(..) => return None,
+    };
+    let lbp = affix.lbp();
+    match lowest_lbp(lhs) {
+        Some(inner) if !needs_parens_left(lhs, lbp) => Some(lbp.min(inner)),
+        _ => Some(lbp),
+    }
+}
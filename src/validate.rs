@@ -0,0 +1,153 @@
+//! A parser which checks the structure of an expression without building
+//! it, see [`PrattParser::validate`].
+
+use crate::{Affix, BindingPower, PrattError, PrattParser, QueryContext, Tail};
+use core::marker::PhantomData;
+
+/// Forwards the classification of inputs to `parser`, but builds `()` for
+/// every expression instead of calling its callbacks.
+pub(crate) struct Validate<'a, T: ?Sized, Inputs, P> {
+    parser: &'a mut T,
+    marker: PhantomData<fn(Inputs, P)>,
+}
+
+impl<'a, T: ?Sized, Inputs, P> Validate<'a, T, Inputs, P> {
+    pub(crate) fn new(parser: &'a mut T) -> Self {
+        Validate {
+            parser,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, Inputs, P> PrattParser<Inputs, P> for Validate<'_, T, Inputs, P>
+where
+    T: PrattParser<Inputs, P> + ?Sized,
+    Inputs: Iterator<Item = T::Input>,
+    P: BindingPower,
+{
+    type Error = T::Error;
+    type Input = T::Input;
+    type Output = ();
+    type Position = T::Position;
+
+    fn query(&mut self, input: &T::Input) -> Result<Affix<P>, T::Error> {
+        self.parser.query(input)
+    }
+
+    fn classify(&mut self, input: &T::Input) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.classify(input)
+    }
+
+    fn query_led(&mut self, input: &T::Input) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.query_led(input)
+    }
+
+    fn query_with_context(
+        &mut self,
+        context: &mut QueryContext<'_, Inputs, P>,
+    ) -> Result<Option<Affix<P>>, T::Error> {
+        self.parser.query_with_context(context)
+    }
+
+    fn primary(&mut self, _: T::Input, _: &mut Tail<'_, Inputs, P>) -> Result<(), T::Error> {
+        Ok(())
+    }
+
+    fn infix(&mut self, _: (), _: T::Input, _: ()) -> Result<(), T::Error> {
+        Ok(())
+    }
+
+    fn prefix(&mut self, _: T::Input, _: ()) -> Result<(), T::Error> {
+        Ok(())
+    }
+
+    #[cfg(feature = "alloc")]
+    fn infix_chain(&mut self, _: (), _: alloc::vec::Vec<(T::Input, ())>) -> Result<(), T::Error> {
+        Ok(())
+    }
+
+    fn postfix(&mut self, _: (), _: T::Input) -> Result<(), T::Error> {
+        Ok(())
+    }
+
+    /// The inputs which `parser` would consume are unknown without calling
+    /// it, so the operator is reported as unsupported instead.
+    fn postfix_with(
+        &mut self,
+        _: (),
+        op: T::Input,
+        _: &mut Tail<'_, Inputs, P>,
+    ) -> Result<(), PrattError<T::Input, T::Error, T::Position>> {
+        let position = self.parser.position(&op);
+        Err(PrattError::UnsupportedAffix(op, position))
+    }
+
+    fn ternary(
+        &mut self,
+        _: (),
+        _: T::Input,
+        _: (),
+        _: T::Input,
+        _: (),
+    ) -> Result<(), PrattError<T::Input, T::Error, T::Position>> {
+        Ok(())
+    }
+
+    fn circumfix(
+        &mut self,
+        _: T::Input,
+        _: (),
+        _: T::Input,
+    ) -> Result<(), PrattError<T::Input, T::Error, T::Position>> {
+        Ok(())
+    }
+
+    #[cfg(feature = "alloc")]
+    fn mixfix(
+        &mut self,
+        _: alloc::vec::Vec<crate::MixfixPart<T::Input, ()>>,
+    ) -> Result<(), PrattError<T::Input, T::Error, T::Position>> {
+        Ok(())
+    }
+
+    fn juxtaposition(&self) -> Option<P> {
+        self.parser.juxtaposition()
+    }
+
+    fn juxtapose(
+        &mut self,
+        _: (),
+        _: (),
+    ) -> Result<(), PrattError<T::Input, T::Error, T::Position>> {
+        Ok(())
+    }
+
+    fn closes(op: &T::Input, delimiter: &T::Input) -> bool {
+        T::closes(op, delimiter)
+    }
+
+    fn on_empty(&mut self) -> Result<Option<()>, T::Error> {
+        Ok(self.parser.on_empty()?.map(|_| ()))
+    }
+
+    fn recover(
+        &mut self,
+        _: &PrattError<T::Input, T::Error, T::Position>,
+        _: &mut Tail<'_, Inputs, P>,
+    ) -> Option<()> {
+        None
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.parser.max_depth()
+    }
+
+    fn position(&self, input: &T::Input) -> T::Position {
+        self.parser.position(input)
+    }
+
+    fn normalize(&self, precedence: P) -> P {
+        self.parser.normalize(precedence)
+    }
+}
@@ -0,0 +1,165 @@
+//! Parsing into a stream of events instead of a tree, see [`PrattVisitor`].
+
+use crate::{led, nud, operand, Affix, BindingPower, PrattError, PrattParser, Precedence, Tail};
+use core::marker::PhantomData;
+
+/// A parser which is told about each operator and operand as it is parsed,
+/// instead of building an output for it.
+///
+/// Events are fired in post-order: the events of an operator's operands
+/// come before the event of the operator itself, so `1+2*3` fires
+/// `primary(1)`, `infix_open(+)`, `primary(2)`, `infix_open(*)`,
+/// `primary(3)`, `infix(*)`, `infix(+)`. An evaluator can therefore keep its
+/// operands on a stack.
+///
+/// Only nilfix, prefix, postfix and infix operators are supported. Inputs
+/// classified as any other affix fail with `PrattError::UnsupportedAffix`.
+pub trait PrattVisitor<Inputs, P = Precedence>
+where
+    P: BindingPower,
+    Inputs: Iterator<Item = Self::Input>,
+{
+    type Error: core::fmt::Display;
+    type Input: core::fmt::Debug;
+    /// Location of an input in the source, see [`PrattParser::Position`].
+    type Position: Default;
+
+    fn query(&mut self, input: &Self::Input) -> Result<Affix<P>, Self::Error>;
+
+    /// Called for a primary expression.
+    fn primary(&mut self, input: Self::Input) -> Result<(), Self::Error>;
+
+    /// Called for an infix operator once its left operand has been parsed,
+    /// before its right operand.
+    fn infix_open(&mut self, _op: &Self::Input) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called for an infix operator once its right operand has been parsed.
+    fn infix(&mut self, op: Self::Input) -> Result<(), Self::Error>;
+
+    /// Called for a prefix operator once its operand has been parsed.
+    fn prefix(&mut self, op: Self::Input) -> Result<(), Self::Error>;
+
+    /// Called for a postfix operator once its operand has been parsed.
+    fn postfix(&mut self, op: Self::Input) -> Result<(), Self::Error>;
+
+    /// Returns the position of `input`, see [`PrattParser::position`].
+    fn position(&self, _input: &Self::Input) -> Self::Position {
+        Self::Position::default()
+    }
+
+    /// Parses `inputs` like `PrattParser::parse`, firing events instead of
+    /// building an output.
+    fn visit(
+        &mut self,
+        inputs: impl IntoIterator<Item = Self::Input, IntoIter = Inputs>,
+    ) -> Result<(), PrattError<Self::Input, Self::Error, Self::Position>>
+    where
+        Self: Sized,
+    {
+        Visit {
+            visitor: self,
+            marker: PhantomData,
+        }
+        .parse(inputs)
+    }
+}
+
+/// Drives a visitor by a parser whose output is `()`.
+struct Visit<'a, V, Inputs, P> {
+    visitor: &'a mut V,
+    marker: PhantomData<fn(Inputs, P)>,
+}
+
+impl<V, Inputs, P> Visit<'_, V, Inputs, P>
+where
+    V: PrattVisitor<Inputs, P>,
+    Inputs: Iterator<Item = V::Input>,
+    P: BindingPower,
+{
+    /// Fails on `head` before any of its operands are visited.
+    fn unsupported(
+        &self,
+        head: V::Input,
+    ) -> Result<(), PrattError<V::Input, V::Error, V::Position>> {
+        let position = self.visitor.position(&head);
+        Err(PrattError::UnsupportedAffix(head, position))
+    }
+}
+
+impl<V, Inputs, P> PrattParser<Inputs, P> for Visit<'_, V, Inputs, P>
+where
+    V: PrattVisitor<Inputs, P>,
+    Inputs: Iterator<Item = V::Input>,
+    P: BindingPower,
+{
+    type Error = V::Error;
+    type Input = V::Input;
+    type Output = ();
+    type Position = V::Position;
+
+    fn query(&mut self, input: &V::Input) -> Result<Affix<P>, V::Error> {
+        self.visitor.query(input)
+    }
+
+    fn primary(&mut self, input: V::Input, _: &mut Tail<'_, Inputs, P>) -> Result<(), V::Error> {
+        self.visitor.primary(input)
+    }
+
+    fn infix(&mut self, _: (), op: V::Input, _: ()) -> Result<(), V::Error> {
+        self.visitor.infix(op)
+    }
+
+    fn prefix(&mut self, op: V::Input, _: ()) -> Result<(), V::Error> {
+        self.visitor.prefix(op)
+    }
+
+    fn postfix(&mut self, _: (), op: V::Input) -> Result<(), V::Error> {
+        self.visitor.postfix(op)
+    }
+
+    fn position(&self, input: &V::Input) -> V::Position {
+        self.visitor.position(input)
+    }
+
+    fn nud(
+        &mut self,
+        head: V::Input,
+        tail: &mut Tail<'_, Inputs, P>,
+        info: Affix<P>,
+    ) -> Result<(), PrattError<V::Input, V::Error, V::Position>> {
+        match info {
+            Affix::Circumfix => self.unsupported(head),
+            #[cfg(feature = "alloc")]
+            Affix::Mixfix(_, _) => self.unsupported(head),
+            _ => nud(self, head, tail, info),
+        }
+    }
+
+    fn led(
+        &mut self,
+        head: V::Input,
+        tail: &mut Tail<'_, Inputs, P>,
+        info: Affix<P>,
+        _: (),
+    ) -> Result<(), PrattError<V::Input, V::Error, V::Position>> {
+        match info {
+            Affix::Infix(precedence, associativity) => {
+                self.visitor
+                    .infix_open(&head)
+                    .map_err(PrattError::UserError)?;
+                let rbp = self.rbp(precedence, associativity);
+                let (head, ()) = operand(self, tail, rbp, head)?;
+                self.visitor.infix(head).map_err(PrattError::UserError)
+            }
+            Affix::Postfix(_) => self.visitor.postfix(head).map_err(PrattError::UserError),
+            Affix::Ternary(_, _) | Affix::PostfixWith(_) | Affix::PostfixOrInfix(_, _) => {
+                self.unsupported(head)
+            }
+            #[cfg(feature = "alloc")]
+            Affix::InfixChain(_) => self.unsupported(head),
+            _ => led(self, head, tail, info, ()),
+        }
+    }
+}